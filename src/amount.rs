@@ -0,0 +1,208 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::RegistryError;
+use crate::price::Quote;
+
+/// An amount of an asset: a number of base units and the precision of the
+/// asset, eg. `Amount::from_sat(150_000_000, 8)` is 1.5 L-BTC.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Amount {
+    units: u64,
+    precision: u8,
+}
+
+impl Amount {
+    /// The amount of `units` base units of an asset with the given precision.
+    pub fn from_sat(units: u64, precision: u8) -> Self {
+        Amount { units, precision }
+    }
+
+    /// Parse an amount in base units, eg. `"150000000"`.
+    pub fn from_sat_str(s: &str, precision: u8) -> Result<Self, RegistryError> {
+        let units = s
+            .trim()
+            .parse()
+            .map_err(|_| RegistryError::InvalidAmount(s.to_string()))?;
+        Ok(Amount::from_sat(units, precision))
+    }
+
+    /// Parse a decimal amount, eg. `"1.5"` or `"1,000.25"`, with no more
+    /// decimals than the precision. Commas are only accepted as thousands
+    /// separators.
+    pub fn from_decimal(s: &str, precision: u8) -> Result<Self, RegistryError> {
+        let invalid = || RegistryError::InvalidAmount(s.to_string());
+        let trimmed = s.trim();
+        let (int, frac) = trimmed.split_once('.').unwrap_or((trimmed, ""));
+        let groups: Vec<&str> = int.split(',').collect();
+        if groups.len() > 1
+            && (!(1..=3).contains(&groups[0].len()) || groups[1..].iter().any(|x| x.len() != 3))
+        {
+            return Err(invalid());
+        }
+        let int = groups.concat();
+        if frac.len() > precision as usize
+            || !int.chars().chain(frac.chars()).all(|x| x.is_ascii_digit())
+            || int.len() + frac.len() == 0
+        {
+            return Err(invalid());
+        }
+        let padded = format!("{}{:0<width$}", int, frac, width = precision as usize);
+        let units = padded.parse().map_err(|_| invalid())?;
+        Ok(Amount::from_sat(units, precision))
+    }
+
+    /// The number of base units.
+    pub fn to_sat(&self) -> u64 {
+        self.units
+    }
+
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// The amount in whole units of the asset, eg. 1.5 for 150000000 units
+    /// with precision 8. Lossy for large amounts.
+    pub fn to_f64(&self) -> f64 {
+        self.units as f64 / 10f64.powi(self.precision as i32)
+    }
+
+    /// The sum of two amounts of the same asset, `None` on overflow or when
+    /// the precisions differ.
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        if self.precision != other.precision {
+            return None;
+        }
+        Some(Amount::from_sat(
+            self.units.checked_add(other.units)?,
+            self.precision,
+        ))
+    }
+
+    /// The difference of two amounts of the same asset, `None` on underflow
+    /// or when the precisions differ.
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        if self.precision != other.precision {
+            return None;
+        }
+        Some(Amount::from_sat(
+            self.units.checked_sub(other.units)?,
+            self.precision,
+        ))
+    }
+
+    /// The value of the amount at the given price per unit, eg. the market
+    /// capitalization of a circulating supply.
+    pub fn value(&self, price: &Quote) -> Quote {
+        Quote {
+            value: self.to_f64() * price.value,
+            currency: price.currency,
+        }
+    }
+}
+
+impl Display for Amount {
+    /// Format the amount with thousand separators and without trailing
+    /// zero decimals, eg. `1,234.5`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let digits = format!(
+            "{:0>width$}",
+            self.units,
+            width = self.precision as usize + 1
+        );
+        let (int, frac) = digits.split_at(digits.len() - self.precision as usize);
+        let frac = frac.trim_end_matches('0');
+        write!(f, "{}", group_thousands(int))?;
+        if !frac.is_empty() {
+            write!(f, ".{}", frac)?;
+        }
+        Ok(())
+    }
+}
+
+/// Insert a comma every three digits of an integer, eg. `1,234,567`.
+pub(crate) fn group_thousands(int: &str) -> String {
+    let mut grouped = String::with_capacity(int.len() + int.len() / 3);
+    for (i, digit) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let decimal =
+            |s: &str, precision: u8| Amount::from_decimal(s, precision).map(|x| x.to_sat());
+        assert_eq!(decimal("1.5", 8).unwrap(), 150_000_000);
+        assert_eq!(decimal(" 1,000.25 ", 8).unwrap(), 100_025_000_000);
+        assert_eq!(decimal("1,000,000", 0).unwrap(), 1_000_000);
+        assert_eq!(decimal("0.00000001", 8).unwrap(), 1);
+        assert_eq!(decimal(".5", 1).unwrap(), 5);
+        assert_eq!(decimal("7.", 2).unwrap(), 700);
+        for invalid in [
+            "",
+            ".",
+            "abc",
+            "-1",
+            "+1",
+            "1.5.3",
+            "1,,0",
+            ",100",
+            "100,",
+            "1,00",
+            "1000,000",
+            "1,000.2,5",
+            "1.123456789",
+        ] {
+            assert!(
+                matches!(decimal(invalid, 8), Err(RegistryError::InvalidAmount(_))),
+                "{}",
+                invalid
+            );
+        }
+        assert!(decimal("1.5", 0).is_err());
+
+        let sat = |s: &str| Amount::from_sat_str(s, 8).map(|x| x.to_sat());
+        assert_eq!(sat(" 150000000\n").unwrap(), 150_000_000);
+        assert!(sat("1.5").is_err());
+        assert!(sat("-1").is_err());
+    }
+
+    #[test]
+    fn overflow() {
+        let max = u64::MAX.to_string();
+        assert_eq!(Amount::from_decimal(&max, 0).unwrap().to_sat(), u64::MAX);
+        assert!(Amount::from_decimal("18446744073709551616", 0).is_err());
+        assert!(Amount::from_decimal("184467440737.09551616", 8).is_err());
+        assert!(Amount::from_sat_str("18446744073709551616", 0).is_err());
+
+        let one = Amount::from_sat(1, 8);
+        assert_eq!(Amount::from_sat(u64::MAX, 8).checked_add(one), None);
+        assert_eq!(Amount::from_sat(0, 8).checked_sub(one), None);
+        assert_eq!(one.checked_add(Amount::from_sat(1, 2)), None);
+        assert_eq!(one.checked_add(one), Some(Amount::from_sat(2, 8)));
+    }
+
+    #[test]
+    fn display() {
+        let display = |units: u64, precision: u8| Amount::from_sat(units, precision).to_string();
+        assert_eq!(display(0, 0), "0");
+        assert_eq!(display(1_234_567, 0), "1,234,567");
+        assert_eq!(display(0, 8), "0");
+        assert_eq!(display(100_000_000, 8), "1");
+        assert_eq!(display(150_000_000, 8), "1.5");
+        assert_eq!(display(1, 8), "0.00000001");
+        assert_eq!(display(123_456_789_012, 2), "1,234,567,890.12");
+        assert_eq!(display(u64::MAX, 8), "184,467,440,737.09551615");
+        assert_eq!(group_thousands("123"), "123");
+        assert_eq!(group_thousands("1234"), "1,234");
+    }
+}
@@ -1,41 +1,103 @@
+use crate::amount::Amount;
 use crate::asset_entry::AssetEntry;
+use crate::price::PriceDefinition;
+use crate::search;
 use elements::AssetId;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
 pub struct Metadata {
     pub amp: Option<bool>,
     pub stablecoin: Option<bool>,
     pub weight: Option<u16>,
+    /// The trading pair used to price the asset, eg. `BTCUSDT`.
     pub pair: Option<String>,
     pub meme: Option<bool>,
+    /// The price provider to use for the asset, eg. `kraken`. Every provider
+    /// is tried when missing.
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// The trading pair of the asset on specific providers, when it differs
+    /// from `pair`, eg. `{ "kraken": "XBTUSDT" }`.
+    #[serde(default)]
+    pub pairs: Option<IndexMap<String, String>>,
+    /// What the price of `pair` means for the asset.
+    #[serde(default)]
+    pub price: Option<PriceDefinition>,
+}
+
+impl Metadata {
+    /// The trading pair of the asset on the given price provider.
+    pub fn pair_for(&self, provider: &str) -> Option<&str> {
+        self.pairs
+            .as_ref()
+            .and_then(|x| x.get(provider))
+            .or(self.pair.as_ref())
+            .map(|x| x.as_str())
+    }
+
+    /// Whether the asset has a trading pair on any price provider.
+    pub fn has_pair(&self) -> bool {
+        self.pair.is_some() || self.pairs.as_ref().is_some_and(|x| !x.is_empty())
+    }
+
+    /// Whether the asset can be priced: pegged, or with a trading pair.
+    pub fn is_priced(&self) -> bool {
+        match self.price {
+            Some(PriceDefinition::Peg { .. }) => true,
+            Some(PriceDefinition::Pair { .. }) => self.has_pair(),
+            None => false,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Asset {
     pub asset_id: AssetId,
     pub asset_entry: Option<AssetEntry>,
-    pub supply: Option<String>,
+    pub supply: Option<Amount>,
     pub metadata: Option<Metadata>,
     pub icon: Option<String>,
 }
 impl Asset {
-    pub fn filter(&self, text: &String) -> bool {
-        if let Some(asset_entry) = self.asset_entry.as_ref() {
-            return self.asset_id.to_string().contains(text)
-                || asset_entry.name.contains(text)
-                || asset_entry
-                    .ticker
-                    .as_ref()
-                    .unwrap_or(&"".to_string())
-                    .contains(text)
-                || asset_entry
-                    .domain()
-                    .unwrap_or(&"".to_string())
-                    .contains(text);
-        } else {
-            return self.asset_id.to_string().contains(text);
-        }
+    /// The precision of the asset, 0 when the entry is missing.
+    pub fn precision(&self) -> u8 {
+        self.asset_entry.as_ref().map_or(0, |x| x.precision)
+    }
+
+    pub fn ticker(&self) -> Option<&str> {
+        self.asset_entry.as_ref().and_then(|x| x.ticker.as_deref())
+    }
+
+    /// The name of the asset, `None` when missing or empty.
+    pub fn name(&self) -> Option<&str> {
+        self.asset_entry
+            .as_ref()
+            .map(|x| x.name.as_str())
+            .filter(|x| !x.is_empty())
+    }
+
+    pub fn domain(&self) -> Option<&str> {
+        self.asset_entry.as_ref().and_then(|x| x.domain())
+    }
+
+    pub fn weight(&self) -> Option<u16> {
+        self.metadata.as_ref().and_then(|x| x.weight)
+    }
+
+    /// Whether the entry verifies against its contract, false for entries
+    /// without contract.
+    pub fn is_verified(&self) -> bool {
+        self.asset_entry
+            .as_ref()
+            .is_some_and(|x| x.verify().verified())
+    }
+
+    /// Whether the asset matches the text, ignoring case and diacritics and
+    /// tolerating a few typos, see `Registry::search` for the ranked search.
+    pub fn filter(&self, text: &str) -> bool {
+        search::matches(self, text)
     }
     pub fn is_amp(&self) -> bool {
         self.metadata
@@ -1,7 +1,10 @@
 use elements::bitcoin::hashes::Hash;
 use elements::{AssetId, ContractHash, OutPoint, Txid};
 use serde::{Deserialize, Serialize};
-use serde_json::{Result, Value};
+use serde_json::Value;
+
+use crate::error::RegistryError;
+use crate::verification::{Check, Status, VerificationReport};
 
 /// Contains informations about an asset, including its asset id, the contract
 /// defining its property, and the transaction that issued the asset.
@@ -54,8 +57,8 @@ pub struct AssetEntry {
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Prevout {
-    txid: Txid,
-    vout: u32,
+    pub txid: Txid,
+    pub vout: u32,
 }
 
 impl Default for Prevout {
@@ -69,8 +72,8 @@ impl Default for Prevout {
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Txin {
-    txid: Txid,
-    vin: u32,
+    pub txid: Txid,
+    pub vin: u32,
 }
 
 impl Default for Txin {
@@ -83,45 +86,299 @@ impl Default for Txin {
 }
 
 impl AssetEntry {
+    /// Whether the entry carries the contract and the issuance prevout needed
+    /// for verification, as entries of the full registry do.
+    pub fn is_full(&self) -> bool {
+        self.contract.is_some() && self.issuance_prevout.is_some()
+    }
+
+    /// Fill the fields missing in this entry with those of the full entry
+    /// `other`, eg. fetched from Esplora. Fields already set are kept.
+    pub fn merge(&mut self, other: AssetEntry) {
+        self.contract = self.contract.take().or(other.contract);
+        self.entity = self.entity.take().or(other.entity);
+        self.issuance_prevout = self.issuance_prevout.take().or(other.issuance_prevout);
+        self.issuance_txin = self.issuance_txin.take().or(other.issuance_txin);
+        self.issuer_pubkey = self.issuer_pubkey.take().or(other.issuer_pubkey);
+        self.version = self.version.take().or(other.version);
+    }
+
     pub fn domain(&self) -> Option<&str> {
         self.entity
             .as_ref()
             .and_then(|x| x.get("domain").and_then(|x| x.as_str()))
     }
 
-    pub(crate) fn contract_string(&self) -> Result<String> {
-        serde_json::to_string(&self.contract).map_err(Into::into)
+    pub(crate) fn contract_string(&self) -> Result<String, RegistryError> {
+        match &self.contract {
+            Some(contract) => Ok(serde_json::to_string(contract)?),
+            None => Err(RegistryError::Verification("missing contract".into())),
+        }
     }
 
-    pub(crate) fn issuance_prevout(&self) -> Result<OutPoint> {
+    pub(crate) fn issuance_prevout(&self) -> Result<OutPoint, RegistryError> {
         if let Some(issuance_prevout) = self.issuance_prevout.clone() {
             Ok(OutPoint::new(issuance_prevout.txid, issuance_prevout.vout))
         } else {
-            Err(serde::de::Error::custom("Invalid issuance"))
+            Err(RegistryError::Verification(
+                "missing issuance prevout".into(),
+            ))
         }
     }
 
     /// Verify information in `self.contract` commits in `self.asset_id`
     /// ensuring the validity of the Contract data. Moreover information in the
     /// first level like `self.name` is verified to be the same of the one in
-    /// the contract `self.contract.name`
-    pub fn verifies(&self) -> Result<bool> {
-        let contract_hash = ContractHash::from_json_contract(&self.contract_string()?)?;
+    /// the contract `self.contract.name`. Returns an error when the contract or
+    /// the issuance prevout are missing, see `AssetEntry::verify` for details.
+    pub fn verifies(&self) -> Result<bool, RegistryError> {
+        let report = self.verify();
+        let missing = report
+            .failures()
+            .find(|x| x.status == Status::Missing)
+            .map(|x| x.detail.clone().unwrap_or(format!("missing {}", x.name)));
+        match missing {
+            Some(reason) => Err(RegistryError::Verification(reason)),
+            None => Ok(report.verified()),
+        }
+    }
 
-        let entropy = AssetId::generate_asset_entropy(self.issuance_prevout()?, contract_hash);
+    /// Verify the entry against its contract, reporting the outcome of each
+    /// check: the contract hash, the asset id committed by the contract and
+    /// the issuance prevout, and each field mirrored from the contract.
+    pub fn verify(&self) -> VerificationReport {
+        let mut checks = vec![];
 
-        let asset_id = AssetId::from_entropy(entropy);
+        let contract_hash = match self.contract_string() {
+            Ok(contract) => match ContractHash::from_json_contract(&contract) {
+                Ok(hash) => {
+                    checks.push(Check::new(
+                        "contract hash",
+                        Status::Pass,
+                        Some(hash.to_string()),
+                    ));
+                    Some(hash)
+                }
+                Err(err) => {
+                    checks.push(Check::new(
+                        "contract hash",
+                        Status::Fail,
+                        Some(err.to_string()),
+                    ));
+                    None
+                }
+            },
+            Err(_) => {
+                checks.push(Check::new(
+                    "contract hash",
+                    Status::Missing,
+                    Some("missing contract".into()),
+                ));
+                None
+            }
+        };
 
-        let ticker = match self.ticker.clone() {
-            Some(val) => Value::String(val),
-            None => Value::Null,
+        let asset_id = match (self.issuance_prevout(), contract_hash) {
+            (Ok(prevout), Some(contract_hash)) => {
+                let entropy = AssetId::generate_asset_entropy(prevout, contract_hash);
+                let asset_id = AssetId::from_entropy(entropy);
+                if asset_id == self.asset_id {
+                    Check::new("asset id", Status::Pass, None)
+                } else {
+                    Check::new(
+                        "asset id",
+                        Status::Fail,
+                        Some(format!("contract commits to {}", asset_id)),
+                    )
+                }
+            }
+            (Err(_), _) => Check::new(
+                "asset id",
+                Status::Missing,
+                Some("missing issuance prevout".into()),
+            ),
+            (_, None) => Check::new("asset id", Status::Missing, Some("missing contract".into())),
         };
-        Ok(asset_id == self.asset_id)
-        //    && Some(self.version as u64) == self.contract["version"].as_u64()
-        //    && Some(self.issuer_pubkey.as_str()) == self.contract["issuer_pubkey"].as_str()
-        //    && Some(self.name.as_str()) == self.contract["name"].as_str()
-        //    && ticker == self.contract["ticker"]
-        //    && Some(self.precision as u64) == self.contract["precision"].as_u64()
-        //    && self.entity == self.contract["entity"])
+        checks.push(asset_id);
+
+        let fields = [
+            ("name", Value::String(self.name.clone())),
+            (
+                "ticker",
+                self.ticker.clone().map_or(Value::Null, Value::String),
+            ),
+            ("precision", Value::from(self.precision)),
+            (
+                "issuer_pubkey",
+                self.issuer_pubkey
+                    .clone()
+                    .map_or(Value::Null, Value::String),
+            ),
+            ("version", self.version.map_or(Value::Null, Value::from)),
+            ("entity", self.entity.clone().unwrap_or(Value::Null)),
+        ];
+        for (name, value) in fields {
+            let check = match self.contract.as_ref() {
+                None => Check::new(name, Status::Missing, None),
+                Some(contract) => {
+                    let expected = contract.get(name).unwrap_or(&Value::Null);
+                    if *expected == value {
+                        Check::new(name, Status::Pass, None)
+                    } else {
+                        Check::new(
+                            name,
+                            Status::Fail,
+                            Some(format!(
+                                "{} in the contract, {} in the registry",
+                                expected, value
+                            )),
+                        )
+                    }
+                }
+            };
+            checks.push(check);
+        }
+
+        VerificationReport {
+            asset_id: self.asset_id,
+            checks,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// The entry of USDt in the full index of the registry.
+    pub(crate) fn usdt() -> AssetEntry {
+        serde_json::from_value(json!({
+            "asset_id": "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2",
+            "contract": {
+                "entity": { "domain": "tether.to" },
+                "issuer_pubkey": "0337cceec0beea0232ebe14cba0197a9fbd45fcf2ec946749de920e71434c2b904",
+                "name": "Tether USD",
+                "precision": 8,
+                "ticker": "USDt",
+                "version": 0
+            },
+            "entity": { "domain": "tether.to" },
+            "issuance_prevout": {
+                "txid": "9596d259270ef5bac0020435e6d859aea633409483ba64e232b8ba04ce288668",
+                "vout": 0
+            },
+            "issuer_pubkey": "0337cceec0beea0232ebe14cba0197a9fbd45fcf2ec946749de920e71434c2b904",
+            "name": "Tether USD",
+            "precision": 8,
+            "ticker": "USDt",
+            "version": 0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn verified() {
+        let entry = usdt();
+        let report = entry.verify();
+        assert!(report.verified(), "{:?}", report);
+        assert_eq!(report.failures().count(), 0);
+        assert_eq!(report.asset_id, entry.asset_id);
+        assert!(entry.verifies().unwrap());
+    }
+
+    #[test]
+    fn merge() {
+        let full = usdt();
+        let mut entry = AssetEntry {
+            asset_id: full.asset_id,
+            entity: Some(json!({ "domain": "tether.io" })),
+            name: full.name.clone(),
+            precision: full.precision,
+            ticker: full.ticker.clone(),
+            ..Default::default()
+        };
+        assert!(!entry.is_full());
+        entry.merge(full.clone());
+        assert!(entry.is_full());
+        assert_eq!(entry.contract, full.contract);
+        assert_eq!(entry.issuance_prevout, full.issuance_prevout);
+        assert_eq!(entry.issuer_pubkey, full.issuer_pubkey);
+        assert_eq!(entry.version, Some(0));
+        // Not clobbered by the full entry
+        assert_eq!(entry.domain(), Some("tether.io"));
+
+        // Nothing to fill
+        let mut entry = usdt();
+        entry.merge(AssetEntry {
+            asset_id: full.asset_id,
+            entity: Some(json!({ "domain": "tether.io" })),
+            ..Default::default()
+        });
+        assert_eq!(entry, full);
+    }
+
+    #[test]
+    fn mismatching_fields() {
+        type Mismatch = fn(&mut AssetEntry);
+        let mismatches: [(&str, Mismatch); 5] = [
+            ("ticker", |x| x.ticker = Some("USDT".into())),
+            ("name", |x| x.name = "Tether".into()),
+            ("precision", |x| x.precision = 2),
+            ("entity", |x| {
+                x.entity = Some(json!({ "domain": "tether.io" }))
+            }),
+            ("issuer_pubkey", |x| x.issuer_pubkey = None),
+        ];
+        for (field, mismatch) in mismatches {
+            let mut entry = usdt();
+            mismatch(&mut entry);
+            let report = entry.verify();
+            assert!(!report.verified());
+            let failures: Vec<&Check> = report.failures().collect();
+            assert_eq!(failures.len(), 1, "{}", field);
+            assert_eq!(failures[0].name, field);
+            assert_eq!(failures[0].status, Status::Fail);
+            // Fails, but all the data is there
+            assert!(!entry.verifies().unwrap());
+        }
+
+        // The contract commits to another asset id
+        let mut entry = usdt();
+        entry.issuance_prevout.as_mut().unwrap().vout = 1;
+        let report = entry.verify();
+        assert_eq!(report.check("asset id").unwrap().status, Status::Fail);
+        assert!(!entry.verifies().unwrap());
+    }
+
+    #[test]
+    fn missing_data() {
+        let mut entry = usdt();
+        entry.contract = None;
+        let report = entry.verify();
+        assert!(!report.verified());
+        assert_eq!(
+            report.check("contract hash").unwrap().status,
+            Status::Missing
+        );
+        assert_eq!(report.check("asset id").unwrap().status, Status::Missing);
+        assert!(report.failures().all(|x| x.status == Status::Missing));
+        assert!(matches!(
+            entry.verifies(),
+            Err(RegistryError::Verification(_))
+        ));
+
+        let mut entry = usdt();
+        entry.issuance_prevout = None;
+        let report = entry.verify();
+        let failures: Vec<&Check> = report.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, "asset id");
+        assert_eq!(failures[0].status, Status::Missing);
+        assert!(matches!(
+            entry.verifies(),
+            Err(RegistryError::Verification(_))
+        ));
     }
 }
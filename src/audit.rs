@@ -0,0 +1,226 @@
+//! Batch verification of the registry: every entry is verified against its
+//! contract, see `AssetEntry::verify`, into an `Audit` report.
+
+use elements::AssetId;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::asset::Asset;
+use crate::csv;
+use crate::error::RegistryError;
+use crate::verification::Status;
+
+/// An entry whose contract doesn't match the registry data.
+#[derive(Clone, Debug, Serialize)]
+pub struct Failure {
+    pub asset_id: AssetId,
+    pub ticker: Option<String>,
+    /// The failing checks, eg. `ticker: "USDt" in the contract, "USDT" in the
+    /// registry`.
+    pub reasons: Vec<String>,
+}
+
+/// The outcome of the verification of a set of assets. Build it at once with
+/// `Audit::run`, or incrementally with `Audit::add`, eg. to report progress.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Audit {
+    /// The number of assets audited.
+    pub total: usize,
+    /// The number of assets verified.
+    pub verified: usize,
+    /// The assets failing verification.
+    pub failures: Vec<Failure>,
+    /// The assets without contract, which can't be verified.
+    pub missing_contract: Vec<AssetId>,
+    /// The assets without issuance prevout, which can't be verified.
+    pub missing_prevout: Vec<AssetId>,
+}
+
+impl Audit {
+    /// Audit the assets.
+    pub fn run<'a>(assets: impl IntoIterator<Item = &'a Asset>) -> Self {
+        let mut audit = Audit::default();
+        assets.into_iter().for_each(|x| audit.add(x));
+        audit
+    }
+
+    /// Verify the asset and add the outcome to the report.
+    pub fn add(&mut self, asset: &Asset) {
+        self.total += 1;
+        let entry = match asset.asset_entry.as_ref() {
+            Some(entry) => entry,
+            None => {
+                self.missing_contract.push(asset.asset_id);
+                self.missing_prevout.push(asset.asset_id);
+                return;
+            }
+        };
+        let report = entry.verify();
+        if report.verified() {
+            self.verified += 1;
+            return;
+        }
+        let reasons: Vec<String> = report
+            .failures()
+            .filter(|x| x.status == Status::Fail)
+            .map(|x| match x.detail.as_ref() {
+                Some(detail) => format!("{}: {}", x.name, detail),
+                None => x.name.to_string(),
+            })
+            .collect();
+        if !reasons.is_empty() {
+            self.failures.push(Failure {
+                asset_id: asset.asset_id,
+                ticker: entry.ticker.clone(),
+                reasons,
+            });
+        }
+        if entry.contract.is_none() {
+            self.missing_contract.push(asset.asset_id);
+        }
+        if entry.issuance_prevout.is_none() {
+            self.missing_prevout.push(asset.asset_id);
+        }
+    }
+
+    /// The number of assets that can't be verified, missing the contract or
+    /// the issuance prevout.
+    pub fn incomplete(&self) -> usize {
+        let mut ids: Vec<&AssetId> = self
+            .missing_contract
+            .iter()
+            .chain(self.missing_prevout.iter())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids.len()
+    }
+
+    pub fn to_json(&self) -> Result<String, RegistryError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// The assets not verified, one per line, with the `asset_id`, `status`
+    /// (`failed` or `missing`) and `reason` columns.
+    pub fn to_csv(&self) -> String {
+        let mut csv = csv::line(&["asset_id", "status", "reason"]);
+        for failure in self.failures.iter() {
+            let id = failure.asset_id.to_string();
+            csv.push_str(&csv::line(&[
+                id.as_str(),
+                "failed",
+                &failure.reasons.join("; "),
+            ]));
+        }
+        // One line per asset, missing the contract, the prevout or both
+        let mut missing: IndexMap<&AssetId, Vec<&str>> = IndexMap::new();
+        for id in self.missing_contract.iter() {
+            missing.entry(id).or_default().push("missing contract");
+        }
+        for id in self.missing_prevout.iter() {
+            missing
+                .entry(id)
+                .or_default()
+                .push("missing issuance prevout");
+        }
+        for (id, reasons) in missing {
+            let id = id.to_string();
+            csv.push_str(&csv::line(&[id.as_str(), "missing", &reasons.join("; ")]));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_entry::tests::usdt;
+    use crate::asset_entry::AssetEntry;
+
+    const LBTC: &str = "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d";
+
+    fn asset(asset_id: AssetId, asset_entry: Option<AssetEntry>) -> Asset {
+        Asset {
+            asset_id,
+            asset_entry,
+            supply: None,
+            metadata: None,
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn add() {
+        let verified = usdt();
+        let mut failed = usdt();
+        failed.ticker = Some("USDT".into());
+        let mut no_contract = usdt();
+        no_contract.asset_id = AssetId::from_slice(&[1; 32]).unwrap();
+        no_contract.contract = None;
+        let mut no_prevout = usdt();
+        no_prevout.asset_id = AssetId::from_slice(&[2; 32]).unwrap();
+        no_prevout.issuance_prevout = None;
+        let lbtc: AssetId = LBTC.parse().unwrap();
+
+        let assets = [
+            asset(verified.asset_id, Some(verified.clone())),
+            asset(failed.asset_id, Some(failed)),
+            asset(no_contract.asset_id, Some(no_contract.clone())),
+            asset(no_prevout.asset_id, Some(no_prevout.clone())),
+            asset(lbtc, None),
+        ];
+        let audit = Audit::run(assets.iter());
+        assert_eq!(audit.total, 5);
+        assert_eq!(audit.verified, 1);
+
+        assert_eq!(audit.failures.len(), 1);
+        let failure = &audit.failures[0];
+        assert_eq!(failure.asset_id, verified.asset_id);
+        assert_eq!(failure.ticker.as_deref(), Some("USDT"));
+        assert_eq!(
+            failure.reasons,
+            ["ticker: \"USDt\" in the contract, \"USDT\" in the registry"]
+        );
+
+        // Missing data is reported apart, not as failure
+        assert_eq!(audit.missing_contract, [no_contract.asset_id, lbtc]);
+        assert_eq!(audit.missing_prevout, [no_prevout.asset_id, lbtc]);
+        // The asset without entry is counted once
+        assert_eq!(audit.incomplete(), 3);
+    }
+
+    #[test]
+    fn to_csv() {
+        let usdt = usdt().asset_id;
+        let lbtc: AssetId = LBTC.parse().unwrap();
+        let audit = Audit {
+            total: 3,
+            verified: 1,
+            failures: vec![Failure {
+                asset_id: usdt,
+                ticker: Some("US,D\"t".into()),
+                reasons: vec![
+                    "ticker: \"USDt\" in the contract, \"US,D\"t\" in the registry".into(),
+                    "name: \"Tether\nUSD\" in the contract".into(),
+                ],
+            }],
+            missing_contract: vec![lbtc],
+            missing_prevout: vec![lbtc],
+        };
+        assert_eq!(audit.incomplete(), 1);
+        assert_eq!(
+            audit.to_csv(),
+            format!(
+                "asset_id,status,reason\n\
+                 {},failed,\"ticker: \"\"USDt\"\" in the contract, \"\"US,D\"\"t\"\" in the registry; \
+                 name: \"\"Tether\nUSD\"\" in the contract\"\n\
+                 {},missing,missing contract; missing issuance prevout\n",
+                usdt, lbtc
+            )
+        );
+
+        let empty = Audit::default();
+        assert_eq!(empty.to_csv(), "asset_id,status,reason\n");
+        assert_eq!(empty.incomplete(), 0);
+    }
+}
@@ -0,0 +1,216 @@
+use async_trait::async_trait;
+use elements::encode;
+use elements::hex::FromHex;
+use elements::{Address, AssetId, BlockHash, Transaction, Txid};
+use serde::{Deserialize, Serialize};
+
+use crate::amount::Amount;
+use crate::asset_entry::AssetEntry;
+use crate::error::RegistryError;
+use crate::http::{HttpClient, MaybeSync, ReqwestClient};
+
+/// The confirmation status of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxStatus {
+    pub confirmed: bool,
+    #[serde(default)]
+    pub block_height: Option<u32>,
+    #[serde(default)]
+    pub block_hash: Option<BlockHash>,
+    #[serde(default)]
+    pub block_time: Option<u64>,
+}
+
+/// A transaction of the history of an address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryTx {
+    pub txid: Txid,
+    pub status: TxStatus,
+}
+
+/// A source of chain data for the assets: registry entries, supply and
+/// transactions.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait ChainBackend: MaybeSync {
+    /// The registry entry of the asset, including contract and issuance.
+    async fn asset(&self, asset_id: AssetId) -> Result<AssetEntry, RegistryError>;
+
+    /// The circulating supply of the asset, in base units.
+    async fn supply(&self, asset_id: AssetId) -> Result<u64, RegistryError>;
+
+    /// The transaction with the given txid.
+    async fn transaction(&self, txid: &Txid) -> Result<Transaction, RegistryError>;
+
+    /// The transactions of the address, newest first.
+    async fn address_history(&self, address: &Address) -> Result<Vec<HistoryTx>, RegistryError>;
+}
+
+/// Blockstream Esplora for Liquid.
+pub const LIQUID_ESPLORA_URL: &str = "https://blockstream.info/liquid/api";
+/// Blockstream Esplora for Liquid testnet.
+pub const LIQUID_TESTNET_ESPLORA_URL: &str = "https://blockstream.info/liquidtestnet/api";
+
+/// `ChainBackend` for the Esplora http api.
+#[derive(Clone, Debug)]
+pub struct Esplora<C = ReqwestClient> {
+    client: C,
+    base_url: String,
+}
+
+impl<C: HttpClient> Esplora<C> {
+    /// `base_url` is the root of the api, eg. `LIQUID_ESPLORA_URL`, a self
+    /// hosted instance or a local mock server.
+    pub fn new(client: C, base_url: &str) -> Self {
+        Esplora {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+}
+
+impl Default for Esplora<ReqwestClient> {
+    fn default() -> Self {
+        Esplora::new(ReqwestClient::default(), LIQUID_ESPLORA_URL)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: HttpClient> ChainBackend for Esplora<C> {
+    async fn asset(&self, asset_id: AssetId) -> Result<AssetEntry, RegistryError> {
+        let url = format!("{}/asset/{}", self.base_url, asset_id);
+        let res = self.client.get(&url).await?;
+        Ok(serde_json::from_slice(&res)?)
+    }
+
+    async fn supply(&self, asset_id: AssetId) -> Result<u64, RegistryError> {
+        let url = format!("{}/asset/{}/supply", self.base_url, asset_id);
+        let supply = self.client.get_text(&url).await?;
+        // In base units, the precision doesn't matter
+        Amount::from_sat_str(&supply, 0).map(|x| x.to_sat())
+    }
+
+    async fn transaction(&self, txid: &Txid) -> Result<Transaction, RegistryError> {
+        let url = format!("{}/tx/{}/hex", self.base_url, txid);
+        let hex = self.client.get_text(&url).await?;
+        let bytes = Vec::<u8>::from_hex(hex.trim())
+            .map_err(|err| RegistryError::InvalidTransaction(err.to_string()))?;
+        encode::deserialize(&bytes)
+            .map_err(|err| RegistryError::InvalidTransaction(err.to_string()))
+    }
+
+    async fn address_history(&self, address: &Address) -> Result<Vec<HistoryTx>, RegistryError> {
+        let url = format!("{}/address/{}/txs", self.base_url, address);
+        let res = self.client.get(&url).await?;
+        Ok(serde_json::from_slice(&res)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use elements::LockTime;
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::http::StubClient;
+
+    const USDT: &str = "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2";
+
+    fn esplora(client: StubClient) -> Esplora<StubClient> {
+        Esplora::new(client, "http://esplora/api/")
+    }
+
+    #[test]
+    fn asset() {
+        let asset_id = AssetId::from_str(USDT).unwrap();
+        let json = format!(
+            r#"{{
+                "asset_id": "{}",
+                "contract": {{ "name": "Tether USD", "ticker": "USDt", "precision": 8 }},
+                "entity": {{ "domain": "tether.to" }},
+                "issuance_prevout": {{ "txid": "{}", "vout": 0 }},
+                "issuer_pubkey": "0337cceec0beea0232ebe14cba0197a9fbd45fcf2ec946749de920e71434c2b904",
+                "name": "Tether USD",
+                "precision": 8,
+                "ticker": "USDt",
+                "version": 0,
+                "chain_stats": {{ "tx_count": 1 }}
+            }}"#,
+            USDT, "9596d259270ef5bac0020435e6d859aea633409483ba64e232b8ba04ce288668"
+        );
+        let client =
+            StubClient::default().with(&format!("http://esplora/api/asset/{}", USDT), &json);
+        let entry = block_on(esplora(client).asset(asset_id)).unwrap();
+        assert_eq!(entry.asset_id, asset_id);
+        assert_eq!(entry.ticker.as_deref(), Some("USDt"));
+        assert_eq!(entry.domain(), Some("tether.to"));
+        assert!(entry.is_full());
+
+        let missing = block_on(esplora(StubClient::default()).asset(asset_id));
+        assert!(matches!(missing, Err(RegistryError::HttpStatus(404))));
+    }
+
+    #[test]
+    fn supply() {
+        let asset_id = AssetId::from_str(USDT).unwrap();
+        let url = format!("http://esplora/api/asset/{}/supply", USDT);
+        let client = StubClient::default().with(&url, "21000000000000\n");
+        assert_eq!(
+            block_on(esplora(client).supply(asset_id)).unwrap(),
+            21_000_000_000_000
+        );
+
+        let client = StubClient::default().with(&url, "confidential");
+        let supply = block_on(esplora(client).supply(asset_id));
+        assert!(matches!(supply, Err(RegistryError::InvalidAmount(_))));
+
+        let client = StubClient::default().with_status(&url, 502);
+        let supply = block_on(esplora(client).supply(asset_id));
+        assert!(matches!(supply, Err(RegistryError::HttpStatus(502))));
+    }
+
+    #[test]
+    fn transaction() {
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        };
+        let txid = tx.txid();
+        let hex: String = encode::serialize(&tx)
+            .iter()
+            .map(|x| format!("{:02x}", x))
+            .collect();
+        let url = format!("http://esplora/api/tx/{}/hex", txid);
+
+        let client = StubClient::default().with(&url, &format!("{}\n", hex));
+        assert_eq!(block_on(esplora(client).transaction(&txid)).unwrap(), tx);
+
+        let client = StubClient::default().with(&url, "not hex");
+        let invalid = block_on(esplora(client).transaction(&txid));
+        assert!(matches!(invalid, Err(RegistryError::InvalidTransaction(_))));
+
+        let client = StubClient::default().with(&url, "0200");
+        let truncated = block_on(esplora(client).transaction(&txid));
+        assert!(matches!(
+            truncated,
+            Err(RegistryError::InvalidTransaction(_))
+        ));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn send() {
+        fn send<T: Send>(_: &T) {}
+        let esplora = Esplora::default();
+        send(&esplora.supply(AssetId::from_str(USDT).unwrap()));
+    }
+}
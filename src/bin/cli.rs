@@ -0,0 +1,315 @@
+//! Query the registry from the command line, without network: from the copies
+//! embedded in the binary, or from local registry files.
+//!
+//! ```text
+//! enciclopedia-cli [options] search <query>
+//! enciclopedia-cli [options] show <asset id>
+//! enciclopedia-cli [options] verify <asset id> | --all
+//! enciclopedia-cli [options] filter main|amp|stablecoins|iconed|meme|all
+//! enciclopedia-cli [options] audit [--csv]
+//! ```
+
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use elements::AssetId;
+use futures::executor::block_on;
+use serde::Serialize;
+
+use enciclopedia::asset::Asset;
+use enciclopedia::error::RegistryError;
+use enciclopedia::export::ExportFormat;
+use enciclopedia::import::{Import, Precedence};
+use enciclopedia::registry::{Filter, Registry};
+use enciclopedia::source::{Format, Source, Sources};
+use enciclopedia::verification::{Status, VerificationReport};
+
+const USAGE: &str = "\
+Usage: enciclopedia-cli [options] <command>
+
+Commands:
+    search <query>                  search the assets, see the query syntax in the README
+    show <asset id>                 show an asset
+    verify <asset id> | --all       verify assets against their contract
+    filter <name>                   list the assets of a preset filter:
+                                    main, amp, stablecoins, iconed, meme or all
+    audit [--csv]                   verify every asset and report the failures and
+                                    the assets missing contract or prevout
+
+Options:
+    --json                          print json instead of text
+    --export csv|json|tokenlist     print the assets of search and filter as csv, as
+                                    index.json or as a token list
+    --registry <file>               load the asset index from a local file instead
+                                    of the embedded copy
+    --format minimal|full           the format of the asset index
+    --metadata <file>               load the metadata from a local file
+    --icons <file>                  load the icons from a local file
+    --import <file>                 merge the metadata file or token list into the
+                                    metadata, replacing the conflicting values
+    --import-fill <file>            merge the metadata file or token list into the
+                                    metadata, keeping the conflicting values
+    -h, --help                      print this help";
+
+/// The command line options shared by the commands.
+struct Options {
+    json: bool,
+    export: Option<ExportFormat>,
+    sources: Sources,
+    /// Merged in order after loading the registry.
+    imports: Vec<Import>,
+}
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut options = Options {
+        json: false,
+        export: None,
+        sources: Sources::default(),
+        imports: vec![],
+    };
+    let mut command = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value of {}", name));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(ExitCode::SUCCESS);
+            }
+            "--json" => options.json = true,
+            "--export" => {
+                options.export =
+                    Some(ExportFormat::from_str(&value(&arg)?).map_err(|x| x.to_string())?)
+            }
+            "--registry" => options.sources.index = read(&value(&arg)?)?,
+            "--metadata" => options.sources.metadata = read(&value(&arg)?)?,
+            "--icons" => options.sources.icons = read(&value(&arg)?)?,
+            "--import" | "--import-fill" => {
+                let path = value(&arg)?;
+                let precedence = match arg.as_str() {
+                    "--import" => Precedence::Override,
+                    _ => Precedence::Fill,
+                };
+                let content =
+                    std::fs::read(&path).map_err(|x| format!("can't read {}: {}", path, x))?;
+                let import = Import::parse(&path, precedence, &content)
+                    .map_err(|x| format!("can't import {}: {}", path, x))?;
+                options.imports.push(import);
+            }
+            "--format" => {
+                options.sources.format =
+                    Format::from_str(&value(&arg)?).map_err(|x| x.to_string())?
+            }
+            _ => command.push(arg),
+        }
+    }
+    let mut registry = block_on(Registry::load(&options.sources)).map_err(|x| x.to_string())?;
+    for conflict in registry.import(&options.imports) {
+        eprintln!(
+            "warning: {} of {} is {}, {} in {}, {} kept",
+            conflict.field,
+            conflict.asset_id,
+            conflict.current,
+            conflict.incoming,
+            conflict.source,
+            if conflict.replaced {
+                &conflict.incoming
+            } else {
+                &conflict.current
+            }
+        );
+    }
+
+    match command
+        .iter()
+        .map(|x| x.as_str())
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["search", query @ ..] if !query.is_empty() => {
+            let filter = query
+                .join(" ")
+                .parse()
+                .map_err(|x: RegistryError| x.to_string())?;
+            list(&registry, filter, &options)
+        }
+        ["filter", name] => {
+            let filter = Filter::preset(name).ok_or(format!(
+                "unknown filter {}, expected one of {}",
+                name,
+                Filter::PRESETS.join(", ")
+            ))?;
+            list(&registry, filter, &options)
+        }
+        ["show", id] => {
+            let asset = find(&registry, id)?;
+            if options.json {
+                print_json(asset)?;
+            } else {
+                show(asset);
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        ["audit"] => audit(&registry, false, &options),
+        ["audit", "--csv"] => audit(&registry, true, &options),
+        ["verify", "--all"] => verify(registry.assets.values().collect(), &options),
+        ["verify", id] => verify(vec![find(&registry, id)?], &options),
+        _ => Err(format!("invalid command\n\n{}", USAGE)),
+    }
+}
+
+/// A registry file read from the local file system.
+fn read(path: &str) -> Result<Source, String> {
+    std::fs::read(path)
+        .map(Source::Bytes)
+        .map_err(|x| format!("can't read {}: {}", path, x))
+}
+
+fn find<'a>(registry: &'a Registry, id: &str) -> Result<&'a Asset, String> {
+    let asset_id = AssetId::from_str(id).map_err(|_| RegistryError::InvalidAssetId(id.into()));
+    asset_id
+        .and_then(|x| block_on(registry.query_by_id(x)))
+        .map_err(|x| x.to_string())
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|x| x.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Print the assets matching the filter, one per line.
+fn list(registry: &Registry, filter: Filter, options: &Options) -> Result<ExitCode, String> {
+    let ids = block_on(registry.query(filter)).map_err(|x| x.to_string())?;
+    let assets: Vec<&Asset> = ids.iter().filter_map(|x| registry.assets.get(x)).collect();
+    if let Some(format) = options.export {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_secs());
+        let export = format
+            .export(&assets, timestamp)
+            .map_err(|x| x.to_string())?;
+        print!("{}", export);
+    } else if options.json {
+        print_json(&assets)?;
+    } else {
+        for asset in assets {
+            println!(
+                "{:<10} {:<32} {:<24} {}",
+                asset.ticker().unwrap_or("-"),
+                asset.name().unwrap_or("-"),
+                asset.domain().unwrap_or("-"),
+                asset.asset_id
+            );
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn show(asset: &Asset) {
+    let flag = |x: bool| if x { "yes" } else { "no" };
+    println!("Asset id:   {}", asset.asset_id);
+    println!("Ticker:     {}", asset.ticker().unwrap_or("-"));
+    println!("Name:       {}", asset.name().unwrap_or("-"));
+    println!("Domain:     {}", asset.domain().unwrap_or("-"));
+    println!("Precision:  {}", asset.precision());
+    if let Some(entry) = asset.asset_entry.as_ref() {
+        if let Some(pubkey) = entry.issuer_pubkey.as_ref() {
+            println!("Issuer:     {}", pubkey);
+        }
+        if let Some(prevout) = entry.issuance_prevout.as_ref() {
+            println!("Prevout:    {}:{}", prevout.txid, prevout.vout);
+        }
+    }
+    println!("Amp:        {}", flag(asset.is_amp()));
+    println!("Stablecoin: {}", flag(asset.is_stablecoin()));
+    println!("Meme:       {}", flag(asset.is_meme()));
+    if let Some(weight) = asset.weight() {
+        println!("Weight:     {}", weight);
+    }
+    println!("Verified:   {}", flag(asset.is_verified()));
+}
+
+/// Verify the assets against their contract, failing when any is not
+/// verified.
+fn verify(assets: Vec<&Asset>, options: &Options) -> Result<ExitCode, String> {
+    let reports: Vec<VerificationReport> = assets
+        .iter()
+        .map(|asset| match asset.asset_entry.as_ref() {
+            Some(entry) => entry.verify(),
+            None => VerificationReport {
+                asset_id: asset.asset_id,
+                checks: vec![],
+            },
+        })
+        .collect();
+    if options.json {
+        print_json(&reports)?;
+    } else {
+        for report in reports.iter() {
+            let status = if report.verified() {
+                "verified"
+            } else {
+                "not verified"
+            };
+            println!("{} {}", report.asset_id, status);
+            for check in report.failures() {
+                let status = match check.status {
+                    Status::Fail => "fail",
+                    _ => "missing",
+                };
+                println!(
+                    "    {:<16} {:<8} {}",
+                    check.name,
+                    status,
+                    check.detail.as_deref().unwrap_or_default()
+                );
+            }
+        }
+    }
+    match reports.iter().all(|x| x.verified()) {
+        true => Ok(ExitCode::SUCCESS),
+        false => Ok(ExitCode::FAILURE),
+    }
+}
+
+/// Print the audit of the registry, failing when any asset is not verified.
+fn audit(registry: &Registry, csv: bool, options: &Options) -> Result<ExitCode, String> {
+    let audit = registry.audit();
+    if csv {
+        print!("{}", audit.to_csv());
+    } else if options.json {
+        print_json(&audit)?;
+    } else {
+        println!("Assets:           {}", audit.total);
+        println!("Verified:         {}", audit.verified);
+        println!("Failed:           {}", audit.failures.len());
+        println!("Missing contract: {}", audit.missing_contract.len());
+        println!("Missing prevout:  {}", audit.missing_prevout.len());
+        for failure in audit.failures.iter() {
+            println!(
+                "{} {}",
+                failure.asset_id,
+                failure.ticker.as_deref().unwrap_or("-")
+            );
+            for reason in failure.reasons.iter() {
+                println!("    {}", reason);
+            }
+        }
+    }
+    match audit.verified == audit.total {
+        true => Ok(ExitCode::SUCCESS),
+        false => Ok(ExitCode::FAILURE),
+    }
+}
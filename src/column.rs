@@ -0,0 +1,295 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use elements::AssetId;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::Asset;
+use crate::error::RegistryError;
+use crate::market::Market;
+
+/// A column of the asset list, to show or to sort by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    AssetId,
+    Domain,
+    Precision,
+    Ticker,
+    Name,
+    Weight,
+    Supply,
+    Price,
+    MarketCap,
+    Verified,
+}
+
+impl Column {
+    /// Every column, in display order.
+    pub const ALL: [Column; 10] = [
+        Column::AssetId,
+        Column::Ticker,
+        Column::Name,
+        Column::Domain,
+        Column::Precision,
+        Column::Weight,
+        Column::Supply,
+        Column::Price,
+        Column::MarketCap,
+        Column::Verified,
+    ];
+
+    /// The columns shown by default.
+    pub const DEFAULT: [Column; 3] = [Column::Ticker, Column::Name, Column::MarketCap];
+
+    /// The header of the column.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Column::AssetId => "Asset ID",
+            Column::Domain => "Domain",
+            Column::Precision => "Precision",
+            Column::Ticker => "Ticker",
+            Column::Name => "Name",
+            Column::Weight => "Weight",
+            Column::Supply => "Supply",
+            Column::Price => "Price",
+            Column::MarketCap => "Market cap",
+            Column::Verified => "Verified",
+        }
+    }
+
+    /// The order a column is first sorted in: largest first for figures,
+    /// alphabetical for text.
+    pub fn default_order(&self) -> Order {
+        match self {
+            Column::Weight
+            | Column::Supply
+            | Column::Price
+            | Column::MarketCap
+            | Column::Verified => Order::Desc,
+            _ => Order::Asc,
+        }
+    }
+
+    fn key(&self, asset: &Asset, market: Option<&Market>) -> Option<Key> {
+        let text = |x: Option<&str>| x.map(|x| Key::Text(x.to_lowercase()));
+        match self {
+            Column::AssetId => Some(Key::Text(asset.asset_id.to_string())),
+            Column::Domain => text(asset.domain()),
+            Column::Precision => asset
+                .asset_entry
+                .as_ref()
+                .map(|x| Key::Number(x.precision.into())),
+            Column::Ticker => text(asset.ticker()),
+            Column::Name => text(asset.name()),
+            Column::Weight => asset.weight().map(|x| Key::Number(x.into())),
+            Column::Supply => market
+                .and_then(|x| x.supply)
+                .map(|x| Key::Number(x.to_f64())),
+            Column::Price => market.and_then(|x| x.price).map(|x| Key::Number(x.value)),
+            Column::MarketCap => market
+                .and_then(|x| x.market_cap())
+                .map(|x| Key::Number(x.value)),
+            Column::Verified => Some(Key::Flag(asset.is_verified())),
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = RegistryError;
+
+    /// Parse a column name, eg. `market_cap`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Column::ALL
+            .iter()
+            .find(|x| x.to_string().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| RegistryError::InvalidQuery(format!("unknown column {}", s)))
+    }
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Column::AssetId => "asset_id",
+            Column::Domain => "domain",
+            Column::Precision => "precision",
+            Column::Ticker => "ticker",
+            Column::Name => "name",
+            Column::Weight => "weight",
+            Column::Supply => "supply",
+            Column::Price => "price",
+            Column::MarketCap => "market_cap",
+            Column::Verified => "verified",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    pub fn reverse(&self) -> Order {
+        match self {
+            Order::Asc => Order::Desc,
+            Order::Desc => Order::Asc,
+        }
+    }
+}
+
+/// The value of an asset in a column, compared to sort the list.
+enum Key {
+    Text(String),
+    Number(f64),
+    Flag(bool),
+}
+
+impl Key {
+    /// A total order, so that sorting is consistent even with `NaN` numbers.
+    fn compare(&self, other: &Key) -> Ordering {
+        match (self, other) {
+            (Key::Text(a), Key::Text(b)) => a.cmp(b),
+            (Key::Number(a), Key::Number(b)) => a.total_cmp(b),
+            (Key::Flag(a), Key::Flag(b)) => a.cmp(b),
+            // The keys of a column are all of the same kind
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+/// The order of the asset list: a column and the direction to sort it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Sort {
+    pub column: Column,
+    pub order: Order,
+}
+
+impl Sort {
+    pub fn new(column: Column, order: Order) -> Self {
+        Sort { column, order }
+    }
+
+    /// The sort after clicking the header of `column`: the same column in
+    /// the reverse order, or another column in its default order.
+    pub fn toggle(current: Option<Sort>, column: Column) -> Sort {
+        match current {
+            Some(sort) if sort.column == column => Sort::new(column, sort.order.reverse()),
+            _ => Sort::new(column, column.default_order()),
+        }
+    }
+
+    /// Compare two assets. Assets without a value in the column, eg. without
+    /// ticker or without fetched market data, come last in both orders.
+    pub fn compare(&self, a: &Asset, b: &Asset, markets: &IndexMap<AssetId, Market>) -> Ordering {
+        let a = self.column.key(a, markets.get(&a.asset_id));
+        let b = self.column.key(b, markets.get(&b.asset_id));
+        self.compare_keys(a.as_ref(), b.as_ref())
+    }
+
+    fn compare_keys(&self, a: Option<&Key>, b: Option<&Key>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                let ordering = a.compare(b);
+                match self.order {
+                    Order::Asc => ordering,
+                    Order::Desc => ordering.reverse(),
+                }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    /// Sort the assets, keeping the registry order of equal assets. The key
+    /// of each asset is computed once, eg. verifying its contract.
+    pub fn sort<'a>(&self, assets: &mut [&'a Asset], markets: &IndexMap<AssetId, Market>) {
+        let mut keyed: Vec<(Option<Key>, &'a Asset)> = assets
+            .iter()
+            .map(|x| (self.column.key(x, markets.get(&x.asset_id)), *x))
+            .collect();
+        keyed.sort_by(|a, b| self.compare_keys(a.0.as_ref(), b.0.as_ref()));
+        for (asset, (_, sorted)) in assets.iter_mut().zip(keyed) {
+            *asset = sorted;
+        }
+    }
+}
+
+impl FromStr for Sort {
+    type Err = RegistryError;
+
+    /// Parse a column name, prefixed by `-` to sort in descending order,
+    /// eg. `-market_cap`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('-') {
+            Some(column) => Ok(Sort::new(column.parse()?, Order::Desc)),
+            None => Ok(Sort::new(s.parse()?, Order::Asc)),
+        }
+    }
+}
+
+impl Display for Sort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.order {
+            Order::Asc => write!(f, "{}", self.column),
+            Order::Desc => write!(f, "-{}", self.column),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_entry::tests::usdt;
+    use crate::asset_entry::AssetEntry;
+
+    fn asset(asset_id: u8, asset_entry: Option<AssetEntry>) -> Asset {
+        Asset {
+            asset_id: AssetId::from_slice(&[asset_id; 32]).unwrap(),
+            asset_entry,
+            supply: None,
+            metadata: None,
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn numbers_total_order() {
+        let mut numbers = [2.0, f64::NAN, -1.0, f64::INFINITY, 0.5];
+        numbers.sort_by(|a, b| Key::Number(*a).compare(&Key::Number(*b)));
+        assert_eq!(&numbers[..4], &[-1.0, 0.5, 2.0, f64::INFINITY]);
+        assert!(numbers[4].is_nan());
+    }
+
+    #[test]
+    fn sort_verified() {
+        let mut unverified = usdt();
+        unverified.ticker = Some("USDT".into());
+        let assets = [
+            asset(1, None),
+            asset(2, Some(unverified)),
+            asset(3, Some(usdt())),
+            asset(4, None),
+        ];
+        let mut sorted: Vec<&Asset> = assets.iter().collect();
+        let ids =
+            |x: &[&Asset]| -> Vec<u8> { x.iter().map(|x| x.asset_id.into_inner()[0]).collect() };
+
+        Sort::new(Column::Verified, Order::Desc).sort(&mut sorted, &IndexMap::new());
+        // Equal assets keep their order
+        assert_eq!(ids(&sorted), [3, 1, 2, 4]);
+        Sort::new(Column::Verified, Order::Asc).sort(&mut sorted, &IndexMap::new());
+        assert_eq!(ids(&sorted), [1, 2, 4, 3]);
+
+        // Assets without value come last in both orders
+        Sort::new(Column::Precision, Order::Desc).sort(&mut sorted, &IndexMap::new());
+        assert_eq!(ids(&sorted), [2, 3, 1, 4]);
+    }
+}
@@ -0,0 +1,16 @@
+//! Minimal CSV writing for the exports of the library.
+
+/// A CSV line of the fields, quoted when needed, ending with a newline.
+pub(crate) fn line<S: AsRef<str>>(fields: &[S]) -> String {
+    let fields: Vec<String> = fields.iter().map(|x| field(x.as_ref())).collect();
+    format!("{}\n", fields.join(","))
+}
+
+/// The field, quoted when it contains a separator, a quote or a newline.
+fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
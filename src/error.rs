@@ -0,0 +1,81 @@
+use std::fmt::{self, Display, Formatter};
+
+use elements::AssetId;
+
+/// Errors returned by the `Registry` methods.
+#[derive(Debug)]
+pub enum RegistryError {
+    /// A registry file or a server response is not valid json, or doesn't
+    /// have the expected shape.
+    Parse(serde_json::Error),
+    /// The given string is not a valid asset id.
+    InvalidAssetId(String),
+    /// The given string is not a valid amount for the asset precision.
+    InvalidAmount(String),
+    /// A server response is not a valid transaction, eg. malformed hex.
+    InvalidTransaction(String),
+    /// The given string is not a valid query, eg. an unknown column to sort
+    /// by.
+    InvalidQuery(String),
+    /// The asset is not in the registry.
+    NotFound(AssetId),
+    /// A registry file has no source, eg. the embedded full index of a build
+    /// without the `full` feature.
+    MissingSource(String),
+    /// The request didn't get a response from the server.
+    Network(reqwest::Error),
+    /// The server replied with a non successful status code.
+    HttpStatus(u16),
+    /// The asset metadata doesn't define a pair to price the asset.
+    NoPricePair(AssetId),
+    /// No price provider could price the asset.
+    Price(String),
+    /// The asset data can't be verified against its contract.
+    Verification(String),
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Parse(err) => write!(f, "invalid registry data: {}", err),
+            RegistryError::InvalidAssetId(id) => write!(f, "invalid asset id {}", id),
+            RegistryError::InvalidAmount(amount) => write!(f, "invalid amount {}", amount),
+            RegistryError::InvalidTransaction(reason) => {
+                write!(f, "invalid transaction: {}", reason)
+            }
+            RegistryError::InvalidQuery(reason) => write!(f, "invalid query: {}", reason),
+            RegistryError::NotFound(id) => write!(f, "asset {} not found", id),
+            RegistryError::MissingSource(file) => write!(f, "{} is not available", file),
+            RegistryError::Network(err) => write!(f, "network error: {}", err),
+            RegistryError::HttpStatus(status) => write!(f, "server replied with status {}", status),
+            RegistryError::NoPricePair(id) => write!(f, "no price pair for asset {}", id),
+            RegistryError::Price(reason) => write!(f, "price unavailable: {}", reason),
+            RegistryError::Verification(reason) => write!(f, "verification failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RegistryError::Parse(err) => Some(err),
+            RegistryError::Network(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for RegistryError {
+    fn from(err: serde_json::Error) -> Self {
+        RegistryError::Parse(err)
+    }
+}
+
+impl From<reqwest::Error> for RegistryError {
+    fn from(err: reqwest::Error) -> Self {
+        match err.status() {
+            Some(status) => RegistryError::HttpStatus(status.as_u16()),
+            None => RegistryError::Network(err),
+        }
+    }
+}
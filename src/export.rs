@@ -0,0 +1,308 @@
+//! Export of lists of assets, eg. the result of `Registry::query`, as CSV, as
+//! the `index.json` of the registry or as a token list.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use elements::AssetId;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::Asset;
+use crate::asset_entry::AssetEntry;
+use crate::csv;
+use crate::error::RegistryError;
+
+/// The formats the assets can be exported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One asset per line: id, ticker, name, domain, precision and the
+    /// enciclopedia metadata.
+    Csv,
+    /// The `index.json` shape of the registry: the entries by asset id.
+    Json,
+    /// A `TokenList`.
+    TokenList,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [
+        ExportFormat::Csv,
+        ExportFormat::Json,
+        ExportFormat::TokenList,
+    ];
+
+    /// The name of the exported file.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "assets.csv",
+            ExportFormat::Json => "index.json",
+            ExportFormat::TokenList => "tokenlist.json",
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Json | ExportFormat::TokenList => "application/json",
+        }
+    }
+
+    /// Export the assets. `timestamp`, in seconds since the unix epoch, is
+    /// the time of the token list, unused by the other formats.
+    pub fn export(&self, assets: &[&Asset], timestamp: u64) -> Result<String, RegistryError> {
+        match self {
+            ExportFormat::Csv => Ok(to_csv(assets)),
+            ExportFormat::Json => to_index_json(assets),
+            ExportFormat::TokenList => TokenList::new("Enciclopedia", timestamp, assets).to_json(),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = RegistryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            "tokenlist" => Ok(ExportFormat::TokenList),
+            _ => Err(RegistryError::InvalidQuery(format!(
+                "unknown export format {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "csv"),
+            ExportFormat::Json => write!(f, "json"),
+            ExportFormat::TokenList => write!(f, "tokenlist"),
+        }
+    }
+}
+
+/// The assets as CSV, one per line.
+pub fn to_csv(assets: &[&Asset]) -> String {
+    let mut csv = csv::line(&[
+        "asset_id",
+        "ticker",
+        "name",
+        "domain",
+        "precision",
+        "amp",
+        "stablecoin",
+        "weight",
+    ]);
+    for asset in assets {
+        csv.push_str(&csv::line(&[
+            asset.asset_id.to_string(),
+            asset.ticker().unwrap_or_default().to_string(),
+            asset.name().unwrap_or_default().to_string(),
+            asset.domain().unwrap_or_default().to_string(),
+            asset.precision().to_string(),
+            asset.is_amp().to_string(),
+            asset.is_stablecoin().to_string(),
+            asset.weight().map(|x| x.to_string()).unwrap_or_default(),
+        ]));
+    }
+    csv
+}
+
+/// The entries of the assets in the `index.json` shape of the registry, so
+/// that the export can be loaded back as a full index. Assets without entry
+/// are skipped.
+pub fn to_index_json(assets: &[&Asset]) -> Result<String, RegistryError> {
+    let entries: IndexMap<AssetId, &AssetEntry> = assets
+        .iter()
+        .filter_map(|x| x.asset_entry.as_ref().map(|entry| (x.asset_id, entry)))
+        .collect();
+    Ok(serde_json::to_string_pretty(&entries)?)
+}
+
+/// A token list in the shape of the [Uniswap token lists](https://tokenlists.org),
+/// adapted for Liquid: tokens are identified by their asset id instead of a
+/// chain id and contract address.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenList {
+    pub name: String,
+    /// The time of the list, as RFC 3339 date and time.
+    pub timestamp: String,
+    pub version: Version,
+    pub tokens: Vec<Token>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Token {
+    /// The asset id, also read as `address` from lists not made for Liquid.
+    #[serde(alias = "address")]
+    pub asset_id: AssetId,
+    /// The ticker.
+    pub symbol: String,
+    pub name: String,
+    /// The precision.
+    pub decimals: u8,
+    /// The icon, as url or data url.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "logoURI")]
+    pub logo_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl TokenList {
+    /// The token list of the assets, at the given time in seconds since the
+    /// unix epoch. Assets without ticker are skipped, a token list requiring
+    /// a symbol.
+    pub fn new(name: &str, timestamp: u64, assets: &[&Asset]) -> Self {
+        let tokens = assets
+            .iter()
+            .filter_map(|asset| {
+                let tags = [
+                    (asset.is_amp(), "amp"),
+                    (asset.is_stablecoin(), "stablecoin"),
+                    (asset.is_meme(), "meme"),
+                ];
+                Some(Token {
+                    asset_id: asset.asset_id,
+                    symbol: asset.ticker()?.to_string(),
+                    name: asset.name().unwrap_or_default().to_string(),
+                    decimals: asset.precision(),
+                    logo_uri: asset
+                        .icon
+                        .as_ref()
+                        .map(|x| format!("data:image/png;base64,{}", x)),
+                    tags: tags
+                        .iter()
+                        .filter(|x| x.0)
+                        .map(|x| x.1.to_string())
+                        .collect(),
+                })
+            })
+            .collect();
+        TokenList {
+            name: name.to_string(),
+            timestamp: rfc3339(timestamp),
+            version: Version {
+                major: 1,
+                minor: 0,
+                patch: 0,
+            },
+            tokens,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, RegistryError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// The RFC 3339 date and time, in UTC, of the seconds since the unix epoch.
+fn rfc3339(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+    // Civil date from the days since the epoch, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::Metadata;
+    use crate::asset_entry::tests::usdt;
+    use crate::registry::Registry;
+    use crate::source::{Format, Source, Sources};
+
+    fn asset(asset_entry: Option<AssetEntry>, metadata: Option<Metadata>) -> Asset {
+        Asset {
+            asset_id: asset_entry
+                .as_ref()
+                .map_or(AssetId::default(), |x| x.asset_id),
+            asset_entry,
+            supply: None,
+            metadata,
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn rfc3339_dates() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(1_709_210_096), "2024-02-29T12:34:56Z");
+        // 2100 is not a leap year
+        assert_eq!(rfc3339(4_107_542_399), "2100-02-28T23:59:59Z");
+        assert_eq!(rfc3339(4_107_542_400), "2100-03-01T00:00:00Z");
+    }
+
+    #[test]
+    fn index_json_round_trip() {
+        let usdt = asset(Some(usdt()), None);
+        let unknown = asset(None, None);
+        let json = ExportFormat::Json.export(&[&usdt, &unknown], 0).unwrap();
+
+        let sources = Sources {
+            format: Format::Full,
+            index: Source::Bytes(json.into_bytes()),
+            ..Default::default()
+        };
+        let registry = futures::executor::block_on(Registry::load(&sources)).unwrap();
+        // The asset without entry is skipped
+        assert_eq!(registry.assets.len(), 1);
+        let loaded = &registry.assets[&usdt.asset_id];
+        assert_eq!(loaded.asset_entry, usdt.asset_entry);
+        assert!(loaded.is_verified());
+    }
+
+    #[test]
+    fn csv_escaping() {
+        let mut entry = usdt();
+        entry.name = "Tether \"USD\", on\nLiquid".into();
+        let metadata = Metadata {
+            amp: Some(false),
+            stablecoin: Some(true),
+            weight: Some(10),
+            ..Default::default()
+        };
+        let usdt = asset(Some(entry), Some(metadata));
+        let unknown = asset(None, None);
+        assert_eq!(
+            to_csv(&[&usdt, &unknown]),
+            format!(
+                "asset_id,ticker,name,domain,precision,amp,stablecoin,weight\n\
+                 {},USDt,\"Tether \"\"USD\"\", on\nLiquid\",tether.to,8,false,true,10\n\
+                 {},,,,0,false,false,\n",
+                usdt.asset_id, unknown.asset_id
+            )
+        );
+    }
+}
@@ -0,0 +1,815 @@
+//! Composable filters over the registry assets and their textual syntax.
+//!
+//! A query is a sequence of terms, all of which must match: free text, ranked
+//! as in `Registry::search`, and `field:value` predicates. Terms are combined
+//! with `OR`, negated with `-` or `NOT` and grouped with parentheses, eg.
+//! `amp:true domain:*.io ticker:L-*` or `stablecoin:true (eur OR usd) -icon:false`.
+//!
+//! Text fields take a pattern, where `*` matches any characters, compared
+//! ignoring case and diacritics. Number fields take a value or a range:
+//! `8`, `>10`, `>=10`, `<5`, `<=5`, `10..50`, `10..` or `..50`. Flags take
+//! `true` or `false`.
+//!
+//! Text with spaces or special characters is quoted, eg. `name:"Tether USD"`,
+//! with `\"` and `\\` for a quote and a backslash within the quotes.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::asset::Asset;
+use crate::error::RegistryError;
+use crate::search::{self, normalize};
+
+/// A filter over the registry assets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    /// Every asset.
+    #[default]
+    All,
+    /// The assets matching the text, see `Registry::search`. Results of a
+    /// query with text are ranked by the text match.
+    Text(String),
+    /// The assets matching a predicate on one of their fields.
+    Field(Predicate),
+    /// The assets matching every filter.
+    And(Vec<Filter>),
+    /// The assets matching any filter.
+    Or(Vec<Filter>),
+    /// The assets not matching the filter.
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    /// The main assets: with a positive weight.
+    pub fn main() -> Self {
+        Filter::Field(Predicate::Weight(Bounds::at_least(1)))
+    }
+
+    pub fn amp() -> Self {
+        Filter::Field(Predicate::Amp(true))
+    }
+
+    pub fn stablecoins() -> Self {
+        Filter::Field(Predicate::Stablecoin(true))
+    }
+
+    pub fn meme() -> Self {
+        Filter::Field(Predicate::Meme(true))
+    }
+
+    /// The assets with an icon.
+    pub fn iconed() -> Self {
+        Filter::Field(Predicate::Icon(true))
+    }
+
+    /// The names of the preset filters, see `Filter::preset`.
+    pub const PRESETS: [&'static str; 6] = ["main", "amp", "stablecoins", "iconed", "meme", "all"];
+
+    /// The preset filter with the given name, eg. `amp` for `Filter::amp`.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "main" => Some(Filter::main()),
+            "amp" => Some(Filter::amp()),
+            "stablecoins" => Some(Filter::stablecoins()),
+            "iconed" => Some(Filter::iconed()),
+            "meme" => Some(Filter::meme()),
+            "all" => Some(Filter::All),
+            _ => None,
+        }
+    }
+
+    /// A filter matching the assets that match both filters.
+    pub fn and(self, other: Filter) -> Self {
+        match (self, other) {
+            (Filter::All, other) | (other, Filter::All) => other,
+            (Filter::And(mut filters), Filter::And(others)) => {
+                filters.extend(others);
+                Filter::And(filters)
+            }
+            (Filter::And(mut filters), other) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            (filter, other) => Filter::And(vec![filter, other]),
+        }
+    }
+
+    pub fn matches(&self, asset: &Asset) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Text(text) => search::matches(asset, text),
+            Filter::Field(predicate) => predicate.matches(asset),
+            Filter::And(filters) => filters.iter().all(|x| x.matches(asset)),
+            Filter::Or(filters) => filters.iter().any(|x| x.matches(asset)),
+            Filter::Not(filter) => !filter.matches(asset),
+        }
+    }
+
+    /// The free text the results are ranked by: the text of the filter, or
+    /// of a term of the filter when it's an `And`.
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Filter::Text(text) if !text.trim().is_empty() => Some(text),
+            Filter::And(filters) => filters.iter().find_map(|x| match x {
+                Filter::Text(text) if !text.trim().is_empty() => Some(text.as_str()),
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = RegistryError;
+
+    /// Parse a query, see the module documentation for the syntax. An empty
+    /// query matches every asset.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        if tokens.is_empty() {
+            return Ok(Filter::All);
+        }
+        let mut parser = Parser { tokens, next: 0 };
+        let filter = parser.or()?;
+        match parser.tokens.get(parser.next) {
+            None => Ok(filter),
+            Some(token) => Err(RegistryError::InvalidQuery(format!("unexpected {}", token))),
+        }
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::All => Ok(()),
+            Filter::Text(text) => write!(f, "{}", quote(text, true)),
+            Filter::Field(predicate) => write!(f, "{}", predicate),
+            Filter::And(filters) => {
+                let terms: Vec<String> = filters
+                    .iter()
+                    .map(|x| match x {
+                        Filter::Or(_) => format!("({})", x),
+                        _ => x.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", terms.join(" "))
+            }
+            Filter::Or(filters) => {
+                let terms: Vec<String> = filters
+                    .iter()
+                    .map(|x| match x {
+                        Filter::And(_) => format!("({})", x),
+                        _ => x.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", terms.join(" OR "))
+            }
+            Filter::Not(filter) => match filter.as_ref() {
+                Filter::And(_) | Filter::Or(_) => write!(f, "-({})", filter),
+                // Negating every word otherwise
+                Filter::Text(text) => write!(f, "-{}", quote(text, false)),
+                _ => write!(f, "-{}", filter),
+            },
+        }
+    }
+}
+
+/// A predicate on a field of an asset, its entry or its metadata.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Predicate {
+    AssetId(Pattern),
+    Ticker(Pattern),
+    Name(Pattern),
+    Domain(Pattern),
+    IssuerPubkey(Pattern),
+    /// The price provider, or one of the providers with a pair.
+    Provider(Pattern),
+    /// The trading pair, or one of the pairs of the providers.
+    Pair(Pattern),
+    Precision(Bounds),
+    Weight(Bounds),
+    Version(Bounds),
+    Amp(bool),
+    Stablecoin(bool),
+    Meme(bool),
+    Icon(bool),
+    /// Whether the entry has a contract, as entries of the full index.
+    Contract(bool),
+    /// Whether the asset can be priced, see `Metadata::is_priced`.
+    Priced(bool),
+    /// Whether the entry verifies against its contract.
+    Verified(bool),
+}
+
+impl Predicate {
+    pub fn matches(&self, asset: &Asset) -> bool {
+        let entry = asset.asset_entry.as_ref();
+        let metadata = asset.metadata.as_ref();
+        let text = |pattern: &Pattern, text: Option<&str>| text.is_some_and(|x| pattern.matches(x));
+        match self {
+            Predicate::AssetId(pattern) => pattern.matches(&asset.asset_id.to_string()),
+            Predicate::Ticker(pattern) => text(pattern, asset.ticker()),
+            Predicate::Name(pattern) => text(pattern, asset.name()),
+            Predicate::Domain(pattern) => text(pattern, asset.domain()),
+            Predicate::IssuerPubkey(pattern) => {
+                text(pattern, entry.and_then(|x| x.issuer_pubkey.as_deref()))
+            }
+            Predicate::Provider(pattern) => metadata.is_some_and(|x| {
+                text(pattern, x.provider.as_deref())
+                    || x.pairs.iter().flatten().any(|(x, _)| pattern.matches(x))
+            }),
+            Predicate::Pair(pattern) => metadata.is_some_and(|x| {
+                text(pattern, x.pair.as_deref())
+                    || x.pairs.iter().flatten().any(|(_, x)| pattern.matches(x))
+            }),
+            Predicate::Precision(bounds) => {
+                entry.is_some_and(|x| bounds.contains(x.precision.into()))
+            }
+            Predicate::Weight(bounds) => asset.weight().is_some_and(|x| bounds.contains(x.into())),
+            Predicate::Version(bounds) => entry
+                .and_then(|x| x.version)
+                .is_some_and(|x| bounds.contains(x.into())),
+            Predicate::Amp(flag) => asset.is_amp() == *flag,
+            Predicate::Stablecoin(flag) => asset.is_stablecoin() == *flag,
+            Predicate::Meme(flag) => asset.is_meme() == *flag,
+            Predicate::Icon(flag) => asset.icon.is_some() == *flag,
+            Predicate::Contract(flag) => entry.is_some_and(|x| x.contract.is_some()) == *flag,
+            Predicate::Priced(flag) => metadata.is_some_and(|x| x.is_priced()) == *flag,
+            Predicate::Verified(flag) => asset.is_verified() == *flag,
+        }
+    }
+
+    /// The name of the field in the query syntax.
+    pub fn field(&self) -> &'static str {
+        match self {
+            Predicate::AssetId(_) => "id",
+            Predicate::Ticker(_) => "ticker",
+            Predicate::Name(_) => "name",
+            Predicate::Domain(_) => "domain",
+            Predicate::IssuerPubkey(_) => "issuer_pubkey",
+            Predicate::Provider(_) => "provider",
+            Predicate::Pair(_) => "pair",
+            Predicate::Precision(_) => "precision",
+            Predicate::Weight(_) => "weight",
+            Predicate::Version(_) => "version",
+            Predicate::Amp(_) => "amp",
+            Predicate::Stablecoin(_) => "stablecoin",
+            Predicate::Meme(_) => "meme",
+            Predicate::Icon(_) => "icon",
+            Predicate::Contract(_) => "contract",
+            Predicate::Priced(_) => "priced",
+            Predicate::Verified(_) => "verified",
+        }
+    }
+
+    /// Parse the value of a `field:value` term.
+    fn parse(field: &str, value: &str) -> Result<Self, RegistryError> {
+        let pattern = || Pattern::new(value);
+        let bounds = || value.parse::<Bounds>();
+        let flag = || match value.to_lowercase().as_str() {
+            "true" | "yes" => Ok(true),
+            "false" | "no" => Ok(false),
+            _ => Err(RegistryError::InvalidQuery(format!(
+                "{} is not true or false",
+                value
+            ))),
+        };
+        Ok(match field.to_lowercase().as_str() {
+            "id" | "asset_id" => Predicate::AssetId(pattern()),
+            "ticker" => Predicate::Ticker(pattern()),
+            "name" => Predicate::Name(pattern()),
+            "domain" => Predicate::Domain(pattern()),
+            "issuer_pubkey" | "issuer" => Predicate::IssuerPubkey(pattern()),
+            "provider" => Predicate::Provider(pattern()),
+            "pair" => Predicate::Pair(pattern()),
+            "precision" => Predicate::Precision(bounds()?),
+            "weight" => Predicate::Weight(bounds()?),
+            "version" => Predicate::Version(bounds()?),
+            "amp" => Predicate::Amp(flag()?),
+            "stablecoin" | "stablecoins" => Predicate::Stablecoin(flag()?),
+            "meme" => Predicate::Meme(flag()?),
+            "icon" | "iconed" => Predicate::Icon(flag()?),
+            "contract" => Predicate::Contract(flag()?),
+            "priced" => Predicate::Priced(flag()?),
+            "verified" => Predicate::Verified(flag()?),
+            _ => {
+                return Err(RegistryError::InvalidQuery(format!(
+                    "unknown field {}",
+                    field
+                )))
+            }
+        })
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Predicate::AssetId(x)
+            | Predicate::Ticker(x)
+            | Predicate::Name(x)
+            | Predicate::Domain(x)
+            | Predicate::IssuerPubkey(x)
+            | Predicate::Provider(x)
+            | Predicate::Pair(x) => quote(&x.0, false),
+            Predicate::Precision(x) | Predicate::Weight(x) | Predicate::Version(x) => x.to_string(),
+            Predicate::Amp(x)
+            | Predicate::Stablecoin(x)
+            | Predicate::Meme(x)
+            | Predicate::Icon(x)
+            | Predicate::Contract(x)
+            | Predicate::Priced(x)
+            | Predicate::Verified(x) => x.to_string(),
+        };
+        write!(f, "{}:{}", self.field(), value)
+    }
+}
+
+/// A pattern over a text field, where `*` matches any characters. The text
+/// must match the whole pattern, ignoring case and diacritics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern(String);
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Self {
+        Pattern(pattern.to_string())
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        let pattern: Vec<char> = normalize(&self.0).chars().collect();
+        let text: Vec<char> = normalize(text).chars().collect();
+        // Backtrack to the last `*` on a mismatch
+        let (mut p, mut t) = (0, 0);
+        let mut star = None;
+        while t < text.len() {
+            if p < pattern.len() && pattern[p] == '*' {
+                star = Some((p, t));
+                p += 1;
+            } else if p < pattern.len() && pattern[p] == text[t] {
+                p += 1;
+                t += 1;
+            } else if let Some((star_p, star_t)) = star {
+                p = star_p + 1;
+                t = star_t + 1;
+                star = Some((star_p, star_t + 1));
+            } else {
+                return false;
+            }
+        }
+        pattern[p..].iter().all(|x| *x == '*')
+    }
+}
+
+/// An inclusive range of values of a number field, open when a bound is
+/// missing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+impl Bounds {
+    pub fn at_least(min: u32) -> Self {
+        Bounds {
+            min: Some(min),
+            max: None,
+        }
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        self.min.is_none_or(|x| value >= x) && self.max.is_none_or(|x| value <= x)
+    }
+}
+
+impl FromStr for Bounds {
+    type Err = RegistryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |x: &str| {
+            x.parse::<u32>()
+                .map_err(|_| RegistryError::InvalidQuery(format!("{} is not a number", x)))
+        };
+        let optional = |x: &str| match x {
+            "" => Ok(None),
+            x => number(x).map(Some),
+        };
+        let (min, max) = if let Some(x) = s.strip_prefix(">=") {
+            (Some(number(x)?), None)
+        } else if let Some(x) = s.strip_prefix("<=") {
+            (None, Some(number(x)?))
+        } else if let Some(x) = s.strip_prefix('>') {
+            (Some(number(x)?.saturating_add(1)), None)
+        } else if let Some(x) = s.strip_prefix('<') {
+            let max = number(x)?
+                .checked_sub(1)
+                .ok_or_else(|| RegistryError::InvalidQuery(format!("{} matches no value", s)))?;
+            (None, Some(max))
+        } else if let Some((min, max)) = s.split_once("..") {
+            (optional(min)?, optional(max)?)
+        } else {
+            let value = number(s)?;
+            (Some(value), Some(value))
+        };
+        Ok(Bounds { min, max })
+    }
+}
+
+impl Display for Bounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min == max => write!(f, "{}", min),
+            (Some(min), Some(max)) => write!(f, "{}..{}", min, max),
+            (Some(min), None) => write!(f, ">={}", min),
+            (None, Some(max)) => write!(f, "<={}", max),
+            (None, None) => write!(f, ".."),
+        }
+    }
+}
+
+/// Quote the text when it wouldn't be parsed back as is: as free text when
+/// `bare`, as a single term otherwise, eg. the value of a `field:value` term.
+fn quote(text: &str, bare: bool) -> String {
+    let special = |x: char| matches!(x, '(' | ')' | '|' | '"');
+    let ambiguous = text
+        .split_whitespace()
+        .any(|x| x.starts_with('-') || x.contains(':') || matches!(x, "OR" | "AND" | "NOT"));
+    let spaced = !bare && text.contains(char::is_whitespace);
+    if text.is_empty() || text.contains(special) || ambiguous || spaced {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Or,
+    Not,
+    /// Free text, or a `field:value` predicate.
+    Term(Option<String>, String),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Or => write!(f, "OR"),
+            Token::Not => write!(f, "NOT"),
+            Token::Term(Some(field), value) => write!(f, "{}:{}", field, value),
+            Token::Term(None, text) => write!(f, "{}", text),
+        }
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, RegistryError> {
+    let mut tokens = vec![];
+    let mut chars = query.chars().peekable();
+    while let Some(&x) = chars.peek() {
+        match x {
+            _ if x.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | '|' => {
+                chars.next();
+                tokens.push(match x {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Or,
+                });
+            }
+            '-' => {
+                chars.next();
+                tokens.push(Token::Not);
+            }
+            _ => {
+                let mut field = None;
+                let mut value = String::new();
+                let mut quoted = false;
+                while let Some(&x) = chars.peek() {
+                    if x.is_whitespace() || x == '(' || x == ')' || x == '|' {
+                        break;
+                    }
+                    chars.next();
+                    match x {
+                        '"' => {
+                            quoted = true;
+                            loop {
+                                match chars.next() {
+                                    Some('"') => break,
+                                    Some('\\') if chars.peek().is_some() => {
+                                        value.extend(chars.next())
+                                    }
+                                    Some(x) => value.push(x),
+                                    None => {
+                                        return Err(RegistryError::InvalidQuery(
+                                            "unterminated quote".into(),
+                                        ))
+                                    }
+                                }
+                            }
+                        }
+                        ':' if field.is_none() && !quoted && !value.is_empty() => {
+                            field = Some(std::mem::take(&mut value));
+                        }
+                        _ => value.push(x),
+                    }
+                }
+                tokens.push(match (field, value.as_str()) {
+                    (None, "OR") if !quoted => Token::Or,
+                    (None, "NOT") if !quoted => Token::Not,
+                    (None, "AND") if !quoted => continue,
+                    (field, _) => Token::Term(field, value),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser of the query tokens: `OR` binds looser than the
+/// implicit `AND` of adjacent terms.
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    fn or(&mut self) -> Result<Filter, RegistryError> {
+        let mut filters = vec![self.and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next += 1;
+            filters.push(self.and()?);
+        }
+        Ok(match filters.len() {
+            1 => filters.remove(0),
+            _ => Filter::Or(filters),
+        })
+    }
+
+    fn and(&mut self) -> Result<Filter, RegistryError> {
+        let mut filters = vec![];
+        // Adjacent words are a single text term
+        let mut text: Option<usize> = None;
+        while !matches!(self.peek(), None | Some(Token::Or) | Some(Token::Close)) {
+            match self.unary()? {
+                Filter::Text(word) => match text {
+                    Some(index) => {
+                        if let Filter::Text(text) = &mut filters[index] {
+                            text.push(' ');
+                            text.push_str(&word);
+                        }
+                    }
+                    None => {
+                        text = Some(filters.len());
+                        filters.push(Filter::Text(word));
+                    }
+                },
+                filter => filters.push(filter),
+            }
+        }
+        match filters.len() {
+            0 => Err(RegistryError::InvalidQuery(match self.peek() {
+                Some(token) => format!("unexpected {}", token),
+                None => "unexpected end of query".into(),
+            })),
+            1 => Ok(filters.remove(0)),
+            _ => Ok(Filter::And(filters)),
+        }
+    }
+
+    fn unary(&mut self) -> Result<Filter, RegistryError> {
+        let token = self.peek().cloned();
+        self.next += 1;
+        match token {
+            Some(Token::Not) => match self.peek() {
+                Some(Token::Open) | Some(Token::Term(..)) | Some(Token::Not) => {
+                    Ok(Filter::Not(Box::new(self.unary()?)))
+                }
+                _ => Err(RegistryError::InvalidQuery("nothing to negate".into())),
+            },
+            Some(Token::Open) => {
+                let filter = self.or()?;
+                match self.peek() {
+                    Some(Token::Close) => {
+                        self.next += 1;
+                        Ok(filter)
+                    }
+                    _ => Err(RegistryError::InvalidQuery("missing )".into())),
+                }
+            }
+            Some(Token::Term(Some(field), value)) => {
+                Ok(Filter::Field(Predicate::parse(&field, &value)?))
+            }
+            Some(Token::Term(None, text)) => Ok(Filter::Text(text)),
+            Some(token) => Err(RegistryError::InvalidQuery(format!("unexpected {}", token))),
+            None => Err(RegistryError::InvalidQuery(
+                "unexpected end of query".into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Registry;
+
+    const LBTC: &str = "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d";
+
+    fn parse(query: &str) -> Filter {
+        query.parse().unwrap()
+    }
+
+    fn text(text: &str) -> Filter {
+        Filter::Text(text.into())
+    }
+
+    fn field(predicate: Predicate) -> Filter {
+        Filter::Field(predicate)
+    }
+
+    fn not(filter: Filter) -> Filter {
+        Filter::Not(Box::new(filter))
+    }
+
+    const README: [&str; 4] = [
+        "usdt",
+        "amp:true domain:*.io ticker:L-*",
+        "stablecoin:true (eur OR usd) -icon:false",
+        "weight:>=10 precision:0..2",
+    ];
+
+    #[test]
+    fn readme_examples() {
+        assert_eq!(parse(README[0]), text("usdt"));
+        assert_eq!(
+            parse(README[1]),
+            Filter::And(vec![
+                field(Predicate::Amp(true)),
+                field(Predicate::Domain(Pattern::new("*.io"))),
+                field(Predicate::Ticker(Pattern::new("L-*"))),
+            ])
+        );
+        assert_eq!(
+            parse(README[2]),
+            Filter::And(vec![
+                field(Predicate::Stablecoin(true)),
+                Filter::Or(vec![text("eur"), text("usd")]),
+                not(field(Predicate::Icon(false))),
+            ])
+        );
+        assert_eq!(
+            parse(README[3]),
+            Filter::And(vec![
+                field(Predicate::Weight(Bounds::at_least(10))),
+                field(Predicate::Precision(Bounds {
+                    min: Some(0),
+                    max: Some(2),
+                })),
+            ])
+        );
+        assert_eq!(parse(""), Filter::All);
+    }
+
+    #[test]
+    fn precedence() {
+        // OR binds looser than the adjacent terms
+        assert_eq!(
+            parse("tether usd OR euro"),
+            Filter::Or(vec![text("tether usd"), text("euro")])
+        );
+        assert_eq!(
+            parse("amp:true eur | usd"),
+            Filter::Or(vec![
+                Filter::And(vec![field(Predicate::Amp(true)), text("eur")]),
+                text("usd"),
+            ])
+        );
+        // Negation binds tighter than both
+        assert_eq!(
+            parse("-eur usd"),
+            Filter::And(vec![not(text("eur")), text("usd")])
+        );
+        assert_eq!(
+            parse("NOT eur OR usd"),
+            Filter::Or(vec![not(text("eur")), text("usd")])
+        );
+        assert_eq!(
+            parse("-(eur OR usd)"),
+            not(Filter::Or(vec![text("eur"), text("usd")]))
+        );
+        assert_eq!(parse("eur AND usd"), text("eur usd"));
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(
+            parse(r#"name:"Tether USD""#),
+            field(Predicate::Name(Pattern::new("Tether USD")))
+        );
+        assert_eq!(parse(r#""OR""#), text("OR"));
+        assert_eq!(parse(r#""ticker:usdt""#), text("ticker:usdt"));
+        assert_eq!(parse(r#""-usdt""#), text("-usdt"));
+        assert_eq!(parse(r#"-"tether usd""#), not(text("tether usd")));
+        assert_eq!(parse(r#""say \"hi\" \\o/""#), text(r#"say "hi" \o/"#));
+        assert_eq!(
+            parse(r#"name:a\b"#),
+            field(Predicate::Name(Pattern::new(r"a\b")))
+        );
+        assert!("\"a\\\"".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for query in README {
+            assert_eq!(parse(query).to_string(), query);
+        }
+        let filters = [
+            text("OR"),
+            text("-usdt"),
+            text("ticker:usdt"),
+            field(Predicate::Name(Pattern::new("Tether USD"))),
+            not(Filter::Or(vec![text("eur"), text("usd")])),
+            not(text("tether usd")),
+            not(text("OR")),
+            not(text("ticker:usdt")),
+            text(r#"say "hi" \o/"#),
+            not(text(r#"a "b""#)),
+            field(Predicate::Name(Pattern::new(r#"The "Tether" \ USD"#))),
+            Filter::Or(vec![
+                Filter::And(vec![field(Predicate::Amp(true)), text("eur")]),
+                field(Predicate::Weight(Bounds {
+                    min: None,
+                    max: Some(5),
+                })),
+            ]),
+        ];
+        for filter in filters {
+            assert_eq!(parse(&filter.to_string()), filter, "{}", filter);
+        }
+    }
+
+    #[test]
+    fn wildcards() {
+        let io = Pattern::new("*.io");
+        assert!(io.matches("blockstream.io"));
+        assert!(io.matches("a.io.b.io"));
+        assert!(!io.matches("io.com"));
+        assert!(!io.matches("a.iox"));
+
+        let lbtc = Pattern::new("L-*");
+        assert!(lbtc.matches("L-BTC"));
+        assert!(lbtc.matches("l-btc"));
+        assert!(lbtc.matches("L-"));
+        assert!(!lbtc.matches("LL-BTC"));
+
+        assert!(Pattern::new("a*b*c").matches("abxbbc"));
+        assert!(!Pattern::new("a*b*c").matches("abxbcb"));
+        assert!(Pattern::new("cafe*").matches("Café Token"));
+        assert!(Pattern::new("*").matches(""));
+    }
+
+    #[test]
+    fn matches() {
+        let registry = Registry::new();
+        let lbtc = registry
+            .assets
+            .values()
+            .find(|x| x.asset_id.to_string() == LBTC)
+            .unwrap();
+        assert!(parse("ticker:L-* -stablecoin:true").matches(lbtc));
+        assert!(parse("usdt OR bitcoin").matches(lbtc));
+        assert!(!parse("-ticker:L-*").matches(lbtc));
+        assert!(!parse("precision:0..2").matches(lbtc));
+    }
+
+    #[test]
+    fn errors() {
+        for query in [
+            "()",
+            "(usdt",
+            "usdt)",
+            "a OR",
+            "OR a",
+            "-",
+            "weight:<0",
+            "weight:ten",
+            "amp:maybe",
+            "color:red",
+            r#"name:"Tether"#,
+        ] {
+            assert!(
+                matches!(query.parse::<Filter>(), Err(RegistryError::InvalidQuery(_))),
+                "{}",
+                query
+            );
+        }
+    }
+}
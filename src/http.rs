@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+
+use crate::error::RegistryError;
+
+/// `Sync` on native targets, where the futures of the async traits of the
+/// crate are `Send`, and any type on wasm, where the browser apis aren't.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSync: Sync {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSync {}
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSync for T {}
+
+/// A minimal HTTP client used for every network request, so they can be
+/// served by another implementation than `reqwest`, eg. a cache or a stub.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait HttpClient: MaybeSync {
+    /// Send a GET request and return the body of the response, failing on
+    /// non successful status codes.
+    async fn get(&self, url: &str) -> Result<Vec<u8>, RegistryError>;
+
+    /// Like `HttpClient::get`, decoding the body as utf8 text.
+    async fn get_text(&self, url: &str) -> Result<String, RegistryError> {
+        let body = self.get(url).await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+/// The default `HttpClient`, backed by `reqwest`.
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestClient {
+    client: reqwest::Client,
+}
+
+impl ReqwestClient {
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestClient { client }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpClient for ReqwestClient {
+    async fn get(&self, url: &str) -> Result<Vec<u8>, RegistryError> {
+        let res = self.client.get(url).send().await?;
+        if !res.status().is_success() {
+            return Err(RegistryError::HttpStatus(res.status().as_u16()));
+        }
+        Ok(res.bytes().await?.to_vec())
+    }
+}
+
+/// An `HttpClient` serving canned responses by url, for the tests of the
+/// modules doing network requests. Unknown urls reply with status 404.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct StubClient {
+    responses: std::collections::HashMap<String, Result<Vec<u8>, u16>>,
+}
+
+#[cfg(test)]
+impl StubClient {
+    /// Reply to `url` with `body`.
+    pub fn with(mut self, url: &str, body: &str) -> Self {
+        self.responses
+            .insert(url.to_string(), Ok(body.as_bytes().to_vec()));
+        self
+    }
+
+    /// Reply to `url` with the non successful `status`.
+    pub fn with_status(mut self, url: &str, status: u16) -> Self {
+        self.responses.insert(url.to_string(), Err(status));
+        self
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpClient for StubClient {
+    async fn get(&self, url: &str) -> Result<Vec<u8>, RegistryError> {
+        match self.responses.get(url) {
+            Some(Ok(body)) => Ok(body.clone()),
+            Some(Err(status)) => Err(RegistryError::HttpStatus(*status)),
+            None => Err(RegistryError::HttpStatus(404)),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Import of additional metadata, from metadata files in the shape of
+//! `liquid_metadatas.json` or from token lists, see `Registry::import`.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use elements::AssetId;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::asset::Metadata;
+use crate::error::RegistryError;
+use crate::export::{Token, TokenList};
+
+/// How the values of an import merge with the values already set, by the
+/// bundled metadata or by the previous imports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precedence {
+    /// The values of the import replace the current ones.
+    #[default]
+    Override,
+    /// The values of the import only fill the missing ones.
+    Fill,
+}
+
+impl Precedence {
+    pub const ALL: [Precedence; 2] = [Precedence::Override, Precedence::Fill];
+}
+
+impl FromStr for Precedence {
+    type Err = RegistryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "override" => Ok(Precedence::Override),
+            "fill" => Ok(Precedence::Fill),
+            _ => Err(RegistryError::InvalidQuery(format!(
+                "unknown precedence {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl Display for Precedence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Precedence::Override => write!(f, "override"),
+            Precedence::Fill => write!(f, "fill"),
+        }
+    }
+}
+
+/// A field of the metadata of an asset set to different values by the
+/// current metadata and by an import.
+#[derive(Clone, Debug, Serialize)]
+pub struct Conflict {
+    pub asset_id: AssetId,
+    /// The field of `Metadata`, eg. `weight`.
+    pub field: &'static str,
+    /// The name of the import.
+    pub source: String,
+    /// The value before the import, as json.
+    pub current: String,
+    /// The value of the import, as json.
+    pub incoming: String,
+    /// Whether the value of the import replaced the current one.
+    pub replaced: bool,
+}
+
+/// The metadata of an external file, merged into the registry with
+/// `Registry::import`.
+#[derive(Clone, Debug)]
+pub struct Import {
+    /// The name of the file, reported in the conflicts.
+    pub name: String,
+    pub precedence: Precedence,
+    pub metadata: IndexMap<AssetId, Metadata>,
+}
+
+impl Import {
+    /// Parse a metadata file, in the shape of `liquid_metadatas.json`, or a
+    /// token list, whose `amp`, `stablecoin` and `meme` tags set the flags of
+    /// the same name.
+    pub fn parse(
+        name: &str,
+        precedence: Precedence,
+        content: &[u8],
+    ) -> Result<Self, RegistryError> {
+        let value: Value = serde_json::from_slice(content)?;
+        let metadata = match value.get("tokens") {
+            Some(_) => {
+                let list: TokenList = serde_json::from_value(value)?;
+                list.tokens
+                    .iter()
+                    .map(|x| (x.asset_id, Self::token_metadata(x)))
+                    .collect()
+            }
+            None => serde_json::from_value(value)?,
+        };
+        Ok(Import {
+            name: name.to_string(),
+            precedence,
+            metadata,
+        })
+    }
+
+    fn token_metadata(token: &Token) -> Metadata {
+        let tag = |name: &str| token.tags.iter().any(|x| x == name).then_some(true);
+        Metadata {
+            amp: tag("amp"),
+            stablecoin: tag("stablecoin"),
+            meme: tag("meme"),
+            ..Default::default()
+        }
+    }
+
+    /// Merge the metadata of the asset in the import into `current`, field
+    /// by field, reporting the conflicting fields.
+    pub(crate) fn merge(
+        &self,
+        asset_id: AssetId,
+        current: &mut Metadata,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        let incoming = match self.metadata.get(&asset_id) {
+            Some(metadata) => metadata.clone(),
+            None => return,
+        };
+        let mut merge = Merge {
+            import: self,
+            asset_id,
+            conflicts,
+        };
+        merge.field("amp", &mut current.amp, incoming.amp);
+        merge.field("stablecoin", &mut current.stablecoin, incoming.stablecoin);
+        merge.field("weight", &mut current.weight, incoming.weight);
+        merge.field("pair", &mut current.pair, incoming.pair);
+        merge.field("meme", &mut current.meme, incoming.meme);
+        merge.field("provider", &mut current.provider, incoming.provider);
+        merge.field("pairs", &mut current.pairs, incoming.pairs);
+        merge.field("price", &mut current.price, incoming.price);
+    }
+}
+
+struct Merge<'a> {
+    import: &'a Import,
+    asset_id: AssetId,
+    conflicts: &'a mut Vec<Conflict>,
+}
+
+impl Merge<'_> {
+    fn field<T: PartialEq + Serialize>(
+        &mut self,
+        field: &'static str,
+        current: &mut Option<T>,
+        incoming: Option<T>,
+    ) {
+        let incoming = match incoming {
+            Some(incoming) => incoming,
+            None => return,
+        };
+        match current {
+            None => *current = Some(incoming),
+            Some(value) if *value == incoming => {}
+            Some(value) => {
+                let replaced = self.import.precedence == Precedence::Override;
+                let json = |x: &T| serde_json::to_string(x).unwrap_or_default();
+                self.conflicts.push(Conflict {
+                    asset_id: self.asset_id,
+                    field,
+                    source: self.import.name.clone(),
+                    current: json(value),
+                    incoming: json(&incoming),
+                    replaced,
+                });
+                if replaced {
+                    *value = incoming;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDT: &str = "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2";
+
+    fn metadata_file(name: &str, precedence: Precedence, weight: u16) -> Import {
+        let content = format!(
+            r#"{{ "{}": {{ "weight": {}, "stablecoin": true }} }}"#,
+            USDT, weight
+        );
+        Import::parse(name, precedence, content.as_bytes()).unwrap()
+    }
+
+    fn merge(imports: &[&Import], current: &mut Metadata) -> Vec<Conflict> {
+        let mut conflicts = vec![];
+        for import in imports {
+            import.merge(USDT.parse().unwrap(), current, &mut conflicts);
+        }
+        conflicts
+    }
+
+    #[test]
+    fn parse() {
+        let list = format!(
+            r#"{{
+                "name": "list",
+                "timestamp": "2024-02-29T12:34:56Z",
+                "version": {{ "major": 1, "minor": 0, "patch": 0 }},
+                "tokens": [{{
+                    "address": "{}",
+                    "symbol": "USDt",
+                    "name": "Tether USD",
+                    "decimals": 8,
+                    "tags": ["stablecoin", "defi"]
+                }}]
+            }}"#,
+            USDT
+        );
+        let import = Import::parse("list", Precedence::Fill, list.as_bytes()).unwrap();
+        assert_eq!(import.name, "list");
+        assert_eq!(import.precedence, Precedence::Fill);
+        let metadata = &import.metadata[&USDT.parse::<AssetId>().unwrap()];
+        assert_eq!(metadata.stablecoin, Some(true));
+        // Missing tags leave the flags unset, not false
+        assert_eq!(metadata.amp, None);
+        assert_eq!(metadata.meme, None);
+        assert_eq!(metadata.weight, None);
+
+        let import = metadata_file("file", Precedence::Override, 10);
+        let metadata = &import.metadata[&USDT.parse::<AssetId>().unwrap()];
+        assert_eq!(metadata.weight, Some(10));
+        assert_eq!(metadata.stablecoin, Some(true));
+        assert_eq!(metadata.amp, None);
+
+        assert!(Import::parse("bad", Precedence::Fill, b"[1, 2]").is_err());
+        assert!(Import::parse("bad", Precedence::Fill, b"{ \"tokens\": 1 }").is_err());
+    }
+
+    #[test]
+    fn precedence() {
+        let current = Metadata {
+            weight: Some(5),
+            ..Default::default()
+        };
+
+        let mut metadata = current.clone();
+        let conflicts = merge(
+            &[&metadata_file("a", Precedence::Override, 10)],
+            &mut metadata,
+        );
+        assert_eq!(metadata.weight, Some(10));
+        // Missing values are filled without conflict
+        assert_eq!(metadata.stablecoin, Some(true));
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.field, "weight");
+        assert_eq!(conflict.source, "a");
+        assert_eq!(conflict.current, "5");
+        assert_eq!(conflict.incoming, "10");
+        assert!(conflict.replaced);
+
+        let mut metadata = current.clone();
+        let conflicts = merge(&[&metadata_file("a", Precedence::Fill, 10)], &mut metadata);
+        assert_eq!(metadata.weight, Some(5));
+        assert_eq!(metadata.stablecoin, Some(true));
+        assert_eq!(conflicts.len(), 1);
+        assert!(!conflicts[0].replaced);
+
+        // Equal values don't conflict
+        let mut metadata = current;
+        let conflicts = merge(
+            &[&metadata_file("a", Precedence::Override, 5)],
+            &mut metadata,
+        );
+        assert_eq!(metadata.weight, Some(5));
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn order() {
+        let a = metadata_file("a", Precedence::Override, 10);
+        let b = metadata_file("b", Precedence::Override, 20);
+
+        let mut metadata = Metadata::default();
+        let conflicts = merge(&[&a, &b], &mut metadata);
+        assert_eq!(metadata.weight, Some(20));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].source, "b");
+
+        let mut metadata = Metadata::default();
+        merge(&[&b, &a], &mut metadata);
+        assert_eq!(metadata.weight, Some(10));
+
+        // A later import filling keeps the values of the previous ones
+        let c = metadata_file("c", Precedence::Fill, 30);
+        let mut metadata = Metadata::default();
+        let conflicts = merge(&[&a, &c], &mut metadata);
+        assert_eq!(metadata.weight, Some(10));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].source, "c");
+        assert!(!conflicts[0].replaced);
+    }
+}
@@ -0,0 +1,357 @@
+use elements::bitcoin::hashes::Hash;
+use elements::confidential;
+use elements::secp256k1_zkp::ZERO_TWEAK;
+use elements::{ContractHash, Transaction};
+use serde::Serialize;
+
+use crate::asset_entry::AssetEntry;
+use crate::backend::ChainBackend;
+use crate::error::RegistryError;
+use crate::verification::{Check, Status};
+
+/// An amount issued in a transaction input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssuedAmount {
+    /// No amount is issued.
+    Null,
+    /// The amount in base units, public on chain.
+    Explicit(u64),
+    /// The amount is blinded.
+    Confidential,
+}
+
+impl From<confidential::Value> for IssuedAmount {
+    fn from(value: confidential::Value) -> Self {
+        match value {
+            confidential::Value::Null => IssuedAmount::Null,
+            confidential::Value::Explicit(amount) => IssuedAmount::Explicit(amount),
+            confidential::Value::Confidential(_) => IssuedAmount::Confidential,
+        }
+    }
+}
+
+/// The result of the verification of an `AssetEntry` against the transaction
+/// that issued the asset.
+#[derive(Clone, Debug, Serialize)]
+pub struct IssuanceReport {
+    pub checks: Vec<Check>,
+    /// The amount of asset issued, if the issuance input was found.
+    pub amount: Option<IssuedAmount>,
+    /// The amount of reissuance tokens issued, if the issuance input was found.
+    pub inflation_keys: Option<IssuedAmount>,
+}
+
+impl IssuanceReport {
+    /// Whether every check passed.
+    pub fn verified(&self) -> bool {
+        !self.checks.is_empty() && self.checks.iter().all(|x| x.status == Status::Pass)
+    }
+}
+
+/// Verify that the input `issuance_txin.vin` of `tx` spends
+/// `issuance_prevout` and issues `asset_id`, committing to the contract of the
+/// entry. `tx` is the transaction `issuance_txin.txid`.
+pub fn verify_issuance(entry: &AssetEntry, tx: &Transaction) -> IssuanceReport {
+    let mut report = IssuanceReport {
+        checks: vec![],
+        amount: None,
+        inflation_keys: None,
+    };
+    let txin = match entry.issuance_txin.as_ref() {
+        Some(txin) => txin,
+        None => {
+            report.checks.push(Check::new(
+                "issuance input",
+                Status::Missing,
+                Some("missing issuance txin".into()),
+            ));
+            return report;
+        }
+    };
+    if tx.txid() != txin.txid {
+        report.checks.push(Check::new(
+            "issuance input",
+            Status::Fail,
+            Some(format!("transaction {} is not {}", tx.txid(), txin.txid)),
+        ));
+        return report;
+    }
+    let input = match tx.input.get(txin.vin as usize) {
+        Some(input) => input,
+        None => {
+            report.checks.push(Check::new(
+                "issuance input",
+                Status::Fail,
+                Some(format!("transaction has no input {}", txin.vin)),
+            ));
+            return report;
+        }
+    };
+    report
+        .checks
+        .push(Check::new("issuance input", Status::Pass, None));
+
+    let prevout = match entry.issuance_prevout.as_ref() {
+        None => Check::new("issuance prevout", Status::Missing, None),
+        Some(prevout)
+            if prevout.txid == input.previous_output.txid
+                && prevout.vout == input.previous_output.vout =>
+        {
+            Check::new("issuance prevout", Status::Pass, None)
+        }
+        Some(_) => Check::new(
+            "issuance prevout",
+            Status::Fail,
+            Some(format!("input spends {}", input.previous_output)),
+        ),
+    };
+    report.checks.push(prevout);
+
+    if !input.has_issuance() {
+        report.checks.push(Check::new(
+            "issuance",
+            Status::Fail,
+            Some("input has no issuance".into()),
+        ));
+        return report;
+    }
+    if input.asset_issuance.asset_blinding_nonce != ZERO_TWEAK {
+        report.checks.push(Check::new(
+            "issuance",
+            Status::Fail,
+            Some("input is a reissuance".into()),
+        ));
+        return report;
+    }
+    report
+        .checks
+        .push(Check::new("issuance", Status::Pass, None));
+    report.amount = Some(input.asset_issuance.amount.into());
+    report.inflation_keys = Some(input.asset_issuance.inflation_keys.into());
+
+    let contract_hash = entry
+        .contract_string()
+        .ok()
+        .and_then(|x| ContractHash::from_json_contract(&x).ok());
+    let contract = match contract_hash {
+        None => Check::new("issuance contract", Status::Missing, None),
+        Some(hash) if hash.to_byte_array() == input.asset_issuance.asset_entropy => {
+            Check::new("issuance contract", Status::Pass, None)
+        }
+        Some(_) => Check::new(
+            "issuance contract",
+            Status::Fail,
+            Some("the issuance commits to another contract".into()),
+        ),
+    };
+    report.checks.push(contract);
+
+    let (asset_id, _) = input.issuance_ids();
+    let issued = if asset_id == entry.asset_id {
+        Check::new("issued asset id", Status::Pass, None)
+    } else {
+        Check::new(
+            "issued asset id",
+            Status::Fail,
+            Some(format!("input issues {}", asset_id)),
+        )
+    };
+    report.checks.push(issued);
+    report
+}
+
+/// Fetch the issuance transaction of the entry from the backend and verify it
+/// with `verify_issuance`.
+pub async fn fetch_and_verify_issuance<B: ChainBackend + ?Sized>(
+    backend: &B,
+    entry: &AssetEntry,
+) -> Result<IssuanceReport, RegistryError> {
+    let txin = entry
+        .issuance_txin
+        .as_ref()
+        .ok_or_else(|| RegistryError::Verification("missing issuance txin".into()))?;
+    let tx = backend.transaction(&txin.txid).await?;
+    Ok(verify_issuance(entry, &tx))
+}
+
+#[cfg(test)]
+mod tests {
+    use elements::encode::serialize_hex;
+    use elements::secp256k1_zkp::PedersenCommitment;
+    use elements::secp256k1_zkp::Tweak;
+    use elements::{AssetIssuance, LockTime, OutPoint, TxIn};
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::asset_entry::tests::usdt;
+    use crate::asset_entry::Txin;
+    use crate::backend::Esplora;
+    use crate::http::StubClient;
+
+    /// A transaction issuing USDt in its second input, and the USDt entry
+    /// pointing to it.
+    fn issuance(amount: confidential::Value) -> (AssetEntry, Transaction) {
+        let mut entry = usdt();
+        let prevout = entry.issuance_prevout.clone().unwrap();
+        let contract_hash = ContractHash::from_json_contract(&entry.contract_string().unwrap());
+        let issuing = TxIn {
+            previous_output: OutPoint::new(prevout.txid, prevout.vout),
+            asset_issuance: AssetIssuance {
+                asset_blinding_nonce: ZERO_TWEAK,
+                asset_entropy: contract_hash.unwrap().to_byte_array(),
+                amount,
+                inflation_keys: confidential::Value::Explicit(1),
+            },
+            ..Default::default()
+        };
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default(), issuing],
+            output: vec![],
+        };
+        entry.issuance_txin = Some(Txin {
+            txid: tx.txid(),
+            vin: 1,
+        });
+        (entry, tx)
+    }
+
+    fn status(report: &IssuanceReport, name: &str) -> Option<Status> {
+        report
+            .checks
+            .iter()
+            .find(|x| x.name == name)
+            .map(|x| x.status)
+    }
+
+    #[test]
+    fn verified() {
+        let (entry, tx) = issuance(confidential::Value::Explicit(1_000));
+        let report = verify_issuance(&entry, &tx);
+        assert!(report.verified(), "{:?}", report);
+        assert_eq!(report.amount, Some(IssuedAmount::Explicit(1_000)));
+        assert_eq!(report.inflation_keys, Some(IssuedAmount::Explicit(1)));
+    }
+
+    #[test]
+    fn confidential_amount() {
+        // The x coordinate of the secp256k1 generator, a valid commitment
+        let commitment = PedersenCommitment::from_slice(&[
+            0x09, 0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce,
+            0x87, 0x0b, 0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81,
+            0x5b, 0x16, 0xf8, 0x17, 0x98,
+        ])
+        .unwrap();
+        let (entry, tx) = issuance(confidential::Value::Confidential(commitment));
+        let report = verify_issuance(&entry, &tx);
+        assert!(report.verified());
+        assert_eq!(report.amount, Some(IssuedAmount::Confidential));
+    }
+
+    #[test]
+    fn wrong_input() {
+        let (mut entry, tx) = issuance(confidential::Value::Explicit(1_000));
+        entry.issuance_txin.as_mut().unwrap().txid = usdt().issuance_prevout.unwrap().txid;
+        let report = verify_issuance(&entry, &tx);
+        assert_eq!(status(&report, "issuance input"), Some(Status::Fail));
+        assert_eq!(report.checks.len(), 1);
+        assert_eq!(report.amount, None);
+
+        let (mut entry, tx) = issuance(confidential::Value::Explicit(1_000));
+        entry.issuance_txin.as_mut().unwrap().vin = 2;
+        let report = verify_issuance(&entry, &tx);
+        assert_eq!(status(&report, "issuance input"), Some(Status::Fail));
+        assert!(!report.verified());
+
+        let (mut entry, tx) = issuance(confidential::Value::Explicit(1_000));
+        entry.issuance_txin = None;
+        let report = verify_issuance(&entry, &tx);
+        assert_eq!(status(&report, "issuance input"), Some(Status::Missing));
+    }
+
+    #[test]
+    fn prevout_mismatch() {
+        let (mut entry, mut tx) = issuance(confidential::Value::Explicit(1_000));
+        tx.input[1].previous_output.vout = 1;
+        entry.issuance_txin.as_mut().unwrap().txid = tx.txid();
+        let report = verify_issuance(&entry, &tx);
+        assert_eq!(status(&report, "issuance input"), Some(Status::Pass));
+        assert_eq!(status(&report, "issuance prevout"), Some(Status::Fail));
+        // Another prevout issues another asset
+        assert_eq!(status(&report, "issued asset id"), Some(Status::Fail));
+    }
+
+    #[test]
+    fn not_an_issuance() {
+        let (mut entry, tx) = issuance(confidential::Value::Explicit(1_000));
+        entry.issuance_txin.as_mut().unwrap().vin = 0;
+        let report = verify_issuance(&entry, &tx);
+        assert_eq!(status(&report, "issuance"), Some(Status::Fail));
+        assert_eq!(report.amount, None);
+
+        let (mut entry, mut tx) = issuance(confidential::Value::Explicit(1_000));
+        tx.input[1].asset_issuance.asset_blinding_nonce = Tweak::from_slice(&[1; 32]).unwrap();
+        entry.issuance_txin.as_mut().unwrap().txid = tx.txid();
+        let report = verify_issuance(&entry, &tx);
+        let reissuance = report.checks.iter().find(|x| x.name == "issuance").unwrap();
+        assert_eq!(reissuance.status, Status::Fail);
+        assert_eq!(reissuance.detail.as_deref(), Some("input is a reissuance"));
+        assert_eq!(report.amount, None);
+    }
+
+    #[test]
+    fn wrong_contract() {
+        let (mut entry, mut tx) = issuance(confidential::Value::Explicit(1_000));
+        tx.input[1].asset_issuance.asset_entropy = [7; 32];
+        entry.issuance_txin.as_mut().unwrap().txid = tx.txid();
+        let report = verify_issuance(&entry, &tx);
+        assert_eq!(status(&report, "issuance"), Some(Status::Pass));
+        assert_eq!(status(&report, "issuance contract"), Some(Status::Fail));
+        assert_eq!(status(&report, "issued asset id"), Some(Status::Fail));
+
+        let (mut entry, tx) = issuance(confidential::Value::Explicit(1_000));
+        entry.contract = None;
+        let report = verify_issuance(&entry, &tx);
+        assert_eq!(status(&report, "issuance contract"), Some(Status::Missing));
+    }
+
+    #[test]
+    fn wrong_asset_id() {
+        let (mut entry, tx) = issuance(confidential::Value::Explicit(1_000));
+        entry.asset_id = elements::AssetId::from_slice(&[3; 32]).unwrap();
+        let report = verify_issuance(&entry, &tx);
+        assert_eq!(status(&report, "issuance contract"), Some(Status::Pass));
+        assert_eq!(status(&report, "issued asset id"), Some(Status::Fail));
+        assert!(!report.verified());
+    }
+
+    #[test]
+    fn fetch() {
+        let (entry, tx) = issuance(confidential::Value::Explicit(1_000));
+        let url = format!("http://esplora/tx/{}/hex", tx.txid());
+
+        let client = StubClient::default().with(&url, &serialize_hex(&tx));
+        let esplora = Esplora::new(client, "http://esplora");
+        let report = block_on(fetch_and_verify_issuance(&esplora, &entry)).unwrap();
+        assert!(report.verified());
+
+        let client = StubClient::default().with(&url, "02000000zz");
+        let esplora = Esplora::new(client, "http://esplora");
+        let malformed = block_on(fetch_and_verify_issuance(&esplora, &entry));
+        assert!(matches!(
+            malformed,
+            Err(RegistryError::InvalidTransaction(_))
+        ));
+
+        let esplora = Esplora::new(StubClient::default(), "http://esplora");
+        let missing = block_on(fetch_and_verify_issuance(&esplora, &entry));
+        assert!(matches!(missing, Err(RegistryError::HttpStatus(404))));
+
+        let mut entry = entry;
+        entry.issuance_txin = None;
+        let no_txin = block_on(fetch_and_verify_issuance(&esplora, &entry));
+        assert!(matches!(no_txin, Err(RegistryError::Verification(_))));
+    }
+}
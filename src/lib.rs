@@ -0,0 +1,26 @@
+//! Liquid asset registry: parsing, querying and verification of the
+//! [Blockstream asset registry](https://github.com/Blockstream/asset_registry_db).
+//!
+//! The library has no dependency on the web client and compiles for both
+//! native targets and `wasm32`.
+
+pub mod amount;
+pub mod asset;
+pub mod asset_entry;
+pub mod audit;
+pub mod backend;
+pub mod column;
+mod csv;
+pub mod error;
+pub mod export;
+pub mod filter;
+pub mod http;
+pub mod import;
+pub mod issuance;
+pub mod market;
+pub mod price;
+pub mod proof;
+pub mod registry;
+pub mod search;
+pub mod source;
+pub mod verification;
@@ -1,45 +1,376 @@
+// The modules of the web client only, apart from the library modules in `src`.
+#[path = "web/store.rs"]
+mod store;
+
 use gloo::console::console_dbg;
+use gloo::events::EventListener;
 use gloo::storage::LocalStorage;
+use gloo::timers::future::TimeoutFuture;
 use gloo_storage::Storage;
-use std::borrow::Borrow;
-use std::error::Error;
-use std::fmt::{self, Debug, Display, Formatter};
-use std::ops::Deref;
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
 use std::str::FromStr;
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
-use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::Array;
 use web_sys::HtmlInputElement as InputElement;
-use web_sys::{EventTarget, HtmlElement, MouseEvent};
-use yew::events::KeyboardEvent;
-use yew::html::Scope;
-use yew::{html, Component, Context, Html, TargetCast};
+use web_sys::HtmlSelectElement;
+use web_sys::{Blob, BlobPropertyBag, Event, HtmlElement, MouseEvent, Url};
+use yew::context::ContextHandle;
+use yew::events::InputEvent;
+use yew::{function_component, html, Component, Context, Html, TargetCast};
+
+use elements::AssetId;
+use enciclopedia::amount::Amount;
+use enciclopedia::asset::Asset;
+use enciclopedia::asset_entry::AssetEntry;
+use enciclopedia::audit::Audit;
+use enciclopedia::backend::{ChainBackend, Esplora, LIQUID_ESPLORA_URL};
+use enciclopedia::column::{Column, Order, Sort};
+use enciclopedia::error::RegistryError;
+use enciclopedia::export::ExportFormat;
+use enciclopedia::http::{HttpClient, ReqwestClient};
+use enciclopedia::import::{Import, Precedence};
+use enciclopedia::issuance::{fetch_and_verify_issuance, IssuanceReport, IssuedAmount};
+use enciclopedia::market::Market;
+use enciclopedia::price::{Currency, PriceOracle, Quote};
+use enciclopedia::proof::ProofVerifier;
+use enciclopedia::registry::Filter;
+use enciclopedia::search;
+use enciclopedia::source::{Format, Source, Sources};
+use enciclopedia::verification::{Status, VerificationReport};
+use store::{LoadState, RegistryProvider, RegistryStore};
+
+/// Registry files refreshed nightly by the `fetch_assets` workflow.
+const INDEX_MINIMAL_URL: &str =
+    "https://raw.githubusercontent.com/lvaccaro/enciclopedia/master/assets/liquid_assets_minimal.json";
+const INDEX_FULL_URL: &str =
+    "https://raw.githubusercontent.com/lvaccaro/enciclopedia/master/assets/liquid_assets.json";
+const ICONS_URL: &str =
+    "https://raw.githubusercontent.com/lvaccaro/enciclopedia/master/assets/liquid_icons.json";
+const METADATA_URL: &str =
+    "https://raw.githubusercontent.com/lvaccaro/enciclopedia/master/assets/liquid_metadatas.json";
+/// Esplora api used for the chain data of the assets.
+const ESPLORA_URL: &str = LIQUID_ESPLORA_URL;
+
+/// The LocalStorage key of the registry format chosen by the user.
+const FORMAT_KEY: &str = "format";
+/// The LocalStorage key of the metadata files imported by the user.
+const IMPORTS_KEY: &str = "imports";
+/// The LocalStorage key of the reference currency chosen by the user.
+const CURRENCY_KEY: &str = "currency";
+/// The LocalStorage keys of the columns shown and of the sort of the list,
+/// also reflected in the `columns` and `sort` parameters of the route.
+const COLUMNS_KEY: &str = "columns";
+const SORT_KEY: &str = "sort";
+/// The parameter of the route with the page of the list, from 1.
+const PAGE_PARAM: &str = "page";
+
+/// The page sizes of the asset list the user can choose from, and the
+/// LocalStorage key of the chosen one.
+const PAGE_SIZES: [usize; 4] = [25, 50, 100, 250];
+const DEFAULT_PAGE_SIZE: usize = 50;
+const PAGE_SIZE_KEY: &str = "page_size";
 
-mod asset;
-mod asset_entry;
-mod registry;
+/// The time to wait after the last keystroke before searching.
+const SEARCH_DEBOUNCE_MS: u32 = 300;
 
-use crate::asset::Asset;
-use crate::registry::{Filter, Registry};
+/// Market data of the listed assets is fetched in batches of
+/// `MARKET_BATCH_SIZE` assets, `MARKET_CONCURRENCY` requests at a time,
+/// waiting `MARKET_BATCH_DELAY_MS` between batches not to hit the rate limits
+/// of Esplora and of the price providers.
+const MARKET_BATCH_SIZE: usize = 8;
+const MARKET_CONCURRENCY: usize = 4;
+const MARKET_BATCH_DELAY_MS: u32 = 1_000;
+
+/// The audit verifies `AUDIT_BATCH_SIZE` assets at a time, yielding to the
+/// browser between batches to render the progress.
+const AUDIT_BATCH_SIZE: usize = 250;
+
+/// The full entries fetched for the assets opened are cached in the
+/// LocalStorage for `ASSET_ENTRY_TTL_SECS`, then fetched again, eg. to follow
+/// updates of the registry.
+const ASSET_ENTRY_TTL_SECS: u64 = 24 * 60 * 60;
 
 /// The possible states a fetch request can be in.
 pub enum FetchState {
     NotFetching,
     Fetching,
-    Success(Vec<&'static Asset>),
-    Single(Asset, Option<String>, Option<String>, bool),
-    Failed(),
+    /// The assets of a query, read from the registry of the store.
+    Success(Vec<AssetId>),
+    Single(Box<AssetDetail>),
+    Failed(RegistryError, Retry),
+}
+
+/// The data shown in the asset dialog.
+pub struct AssetDetail {
+    asset: Asset,
+    supply: Option<Amount>,
+    price: Option<Quote>,
+    report: Option<VerificationReport>,
+    /// Whether the domain ownership proof is valid, or why it couldn't be
+    /// checked, eg. the issuer domain doesn't allow cross origin requests.
+    /// `None` without a full entry.
+    domain_proof: Option<Result<bool, RegistryError>>,
+    issuance: Option<IssuanceReport>,
+}
+
+/// The request to send again when the user retries a failed fetch.
+#[derive(Clone)]
+pub enum Retry {
+    Registry,
+    Assets(Filter),
+    Asset(String),
+}
+
+impl From<Retry> for Msg {
+    fn from(retry: Retry) -> Self {
+        match retry {
+            Retry::Registry => Msg::LoadRegistry,
+            Retry::Assets(filter) => Msg::GetAssets(filter),
+            Retry::Asset(id) => Msg::GetAsset(id),
+        }
+    }
+}
+
+/// The views of the app, addressed by the hash of the url so that they can
+/// be linked, reloaded and navigated with the browser history. List routes
+/// also carry the `columns`, `sort` and `page` parameters of the list.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Route {
+    /// `#/asset/<id>`
+    Asset(String),
+    /// `#/filter/<name>`, the filter of a button, eg. `#/filter/amp`. `#/` is
+    /// the main filter.
+    Filter(String),
+    /// `#/search?q=<query>`
+    Search(String),
+    /// `#/audit`, the verification of the whole registry.
+    Audit,
+    /// `#/sources`, the metadata files imported by the user.
+    Sources,
+}
+
+impl Route {
+    /// Parse the hash of the url, eg. `#/search?q=usdt&sort=-weight`, into
+    /// the route and its parameters. Unknown paths are the main filter.
+    fn parse(hash: &str) -> (Route, IndexMap<String, String>) {
+        let hash = hash.trim_start_matches('#').trim_start_matches('/');
+        let (path, query) = hash.split_once('?').unwrap_or((hash, ""));
+        let params: IndexMap<String, String> = query
+            .split('&')
+            .filter_map(|x| x.split_once('='))
+            .map(|(key, value)| (decode(key), decode(value)))
+            .collect();
+        let route = match path.split_once('/') {
+            Some(("asset", id)) => Route::Asset(id.to_string()),
+            Some(("filter", name)) => Route::Filter(name.to_string()),
+            _ if path == "search" => Route::Search(params.get("q").cloned().unwrap_or_default()),
+            _ if path == "audit" => Route::Audit,
+            _ if path == "sources" => Route::Sources,
+            _ => Route::Filter("main".into()),
+        };
+        (route, params)
+    }
+
+    /// Whether the route shows the asset list.
+    fn is_list(&self) -> bool {
+        matches!(self, Route::Filter(_) | Route::Search(_))
+    }
+
+    /// The hash of the route, without the list parameters.
+    fn path(&self) -> String {
+        match self {
+            Route::Asset(id) => format!("#/asset/{}", id),
+            Route::Filter(name) => format!("#/filter/{}", name),
+            Route::Search(query) => format!("#/search?q={}", encode(query)),
+            Route::Audit => "#/audit".into(),
+            Route::Sources => "#/sources".into(),
+        }
+    }
+}
+
+/// Percent-encode a parameter of the route.
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|x| match x {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~'
+            | b'*'
+            | b':'
+            | b',' => (x as char).to_string(),
+            _ => format!("%{:02X}", x),
+        })
+        .collect()
+}
+
+/// Decode a percent-encoded parameter of the route, `+` being a space.
+fn decode(text: &str) -> String {
+    let mut bytes = vec![];
+    let mut chars = text.bytes();
+    while let Some(x) = chars.next() {
+        match x {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = chars.by_ref().take(2).collect();
+                match std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|x| u8::from_str_radix(x, 16).ok())
+                {
+                    Some(x) => bytes.push(x),
+                    None => {
+                        bytes.push(b'%');
+                        bytes.extend(hex);
+                    }
+                }
+            }
+            x => bytes.push(x),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
 }
 
 enum Msg {
+    LoadRegistry,
+    /// The registry store changed.
+    SetRegistry(RegistryStore),
+    SetFormat(Format),
     SetMarkdownFetchState(FetchState),
-    GetVisibleAssets(),
+    /// Go to the route, adding it to the browser history.
+    Navigate(Route),
+    /// The url changed, eg. with the browser Back and Forward buttons.
+    Route,
     GetAssets(Filter),
+    /// The result of the query with the given sequence number, a list of
+    /// assets or the details of an asset.
+    SetAssets(u64, FetchState),
+    SetSearch(String),
+    Search(u64),
     GetAsset(String),
-    GetError,
+    /// The entry fetched by the query with the given sequence number.
+    SetAssetEntry(u64, AssetId, Option<Box<AssetEntry>>),
+    /// Fetch the details of the asset for the query with the given sequence
+    /// number.
+    ShowAsset(u64, AssetId),
+    SetCurrency(Currency),
+    SetPrice(AssetId, Option<Quote>),
+    FetchMarkets,
+    SetMarkets(IndexMap<AssetId, Market>, Currency),
+    ToggleColumn(Column),
+    SortBy(Column),
+    SetPage(usize),
+    SetPageSize(usize),
+    /// Start the audit of the registry.
+    StartAudit,
+    /// Verify the next batch of the audit of the registry of the given
+    /// generation.
+    AuditBatch(u64),
+    Download(Download),
+    /// Fetch the enabled imports and merge them into the registry.
+    LoadImports,
+    /// The parsed imports of the load with the given sequence number, and
+    /// the errors of the imports that failed by name.
+    SetImports(u64, Vec<Import>, IndexMap<String, String>),
+    AddImport(ImportSource),
+    /// The file of the given name couldn't be read.
+    ImportError(String, String),
+    /// Read the uploaded file as a new import.
+    UploadImport(web_sys::File),
+    SetImportUrl(String),
+    ToggleImport(usize),
+    SetPrecedence(usize, Precedence),
+    /// Merge the import before the previous one.
+    MoveImport(usize),
+    RemoveImport(usize),
+}
+
+/// A metadata file or token list imported by the user, see `Import`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ImportSource {
+    name: String,
+    origin: Origin,
+    precedence: Precedence,
+    enabled: bool,
+}
+
+/// A full entry cached in the LocalStorage, with the time it was fetched at
+/// in seconds since the unix epoch.
+#[derive(Serialize, Deserialize)]
+struct CachedEntry {
+    fetched: u64,
+    entry: AssetEntry,
+}
+
+/// Where an import is read from.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Origin {
+    Url(String),
+    /// The content of an uploaded file.
+    File(String),
+}
+
+/// The files the user can download.
+#[derive(Clone, Copy)]
+pub enum Download {
+    AuditJson,
+    AuditCsv,
+    /// The assets of the current query, in the order of the list.
+    Assets(ExportFormat),
 }
 struct App {
     state: FetchState,
+    route: Route,
+    /// The last list route, where the Back button of an asset goes.
+    list_route: Route,
+    /// The hash of the url last written or handled.
+    url: String,
+    _listeners: Vec<EventListener>,
+    store: RegistryStore,
+    _store_handle: ContextHandle<RegistryStore>,
+    /// The asset to show once its fetched entry is merged in the store, with
+    /// the sequence number of its query.
+    pending_asset: Option<(u64, AssetId)>,
+    /// The audit of the registry, complete when it covers every asset.
+    audit: Option<Audit>,
+    /// The imports, merged in order.
+    imports: Vec<ImportSource>,
+    /// The errors of the imports that couldn't be fetched or parsed, by name.
+    import_errors: IndexMap<String, String>,
+    /// Why the imports couldn't be saved in the local storage, eg. uploaded
+    /// files beyond the quota.
+    save_error: Option<String>,
+    /// Why the last download failed, eg. the browser couldn't create the file.
+    download_error: Option<String>,
+    /// The url in the import field.
+    import_url: String,
+    /// Incremented on each load of the imports, to ignore stale loads.
+    import_seq: u64,
+    backend: Rc<dyn ChainBackend>,
+    oracle: Rc<PriceOracle>,
+    currency: Currency,
+    /// Market data of the listed assets, in the reference currency.
+    markets: IndexMap<AssetId, Market>,
+    /// The assets waiting for their market data to be fetched.
+    market_queue: Vec<AssetId>,
+    fetching_markets: bool,
+    columns: Vec<Column>,
+    sort: Option<Sort>,
+    /// The page of the asset list shown, from 0.
+    page: usize,
+    page_size: usize,
+    /// The text of the search box.
+    search: String,
+    /// The free text of the current query, highlighted in the list.
+    highlight: String,
+    /// Incremented on each keystroke, to search only after the last one.
+    search_seq: u64,
+    /// Incremented on each query, to ignore the results of stale queries.
+    query_seq: u64,
 }
 
 impl Component for App {
@@ -48,104 +379,550 @@ impl Component for App {
 
     fn create(ctx: &Context<Self>) -> Self {
         console_dbg!("create");
-        ctx.link().send_message(Msg::GetAssets(Filter::Main));
+        let (store, store_handle) = ctx
+            .link()
+            .context::<RegistryStore>(ctx.link().callback(Msg::SetRegistry))
+            .expect("App is rendered within a RegistryProvider");
+        let window = gloo::utils::window();
+        let listeners = ["popstate", "hashchange"]
+            .iter()
+            .map(|event| {
+                let link = ctx.link().clone();
+                EventListener::new(&window, *event, move |_| link.send_message(Msg::Route))
+            })
+            .collect();
+        // The route parameters take precedence over the stored choice
+        let url = window.location().hash().unwrap_or_default();
+        let (route, params) = Route::parse(&url);
+        let columns = params
+            .get(COLUMNS_KEY)
+            .map(|x| x.split(',').filter_map(|x| x.parse().ok()).collect())
+            .or_else(|| LocalStorage::get(COLUMNS_KEY).ok())
+            .unwrap_or_else(|| Column::DEFAULT.to_vec());
+        let sort = params
+            .get(SORT_KEY)
+            .and_then(|x| x.parse().ok())
+            .or_else(|| LocalStorage::get(SORT_KEY).ok());
+        let page = Self::page_param(&params);
+        ctx.link().send_message(Msg::LoadImports);
         Self {
             state: FetchState::NotFetching,
+            list_route: match route.is_list() {
+                true => route.clone(),
+                false => Route::Filter("main".into()),
+            },
+            route,
+            url,
+            _listeners: listeners,
+            store,
+            _store_handle: store_handle,
+            pending_asset: None,
+            audit: None,
+            imports: LocalStorage::get(IMPORTS_KEY).unwrap_or_default(),
+            import_errors: IndexMap::default(),
+            save_error: None,
+            download_error: None,
+            import_url: String::new(),
+            import_seq: 0,
+            backend: Rc::new(Esplora::new(ReqwestClient::default(), ESPLORA_URL)),
+            oracle: Rc::new(PriceOracle::default()),
+            currency: LocalStorage::get(CURRENCY_KEY).unwrap_or(Currency::Usd),
+            markets: IndexMap::default(),
+            market_queue: vec![],
+            fetching_markets: false,
+            columns,
+            sort,
+            page,
+            // A size not offered, eg. 0 from a tampered storage, would break the pager
+            page_size: LocalStorage::get(PAGE_SIZE_KEY)
+                .ok()
+                .filter(|x| PAGE_SIZES.contains(x))
+                .unwrap_or(DEFAULT_PAGE_SIZE),
+            search: String::new(),
+            highlight: String::new(),
+            search_seq: 0,
+            query_seq: 0,
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let registry = self.store.registry.clone();
+        let backend = self.backend.clone();
+        let oracle = self.oracle.clone();
+        let currency = self.currency;
         match msg {
+            Msg::LoadRegistry => {
+                self.store.reload();
+                false
+            }
+            Msg::SetRegistry(store) => {
+                let loaded = store.generation != self.store.generation;
+                self.store = store;
+                if loaded {
+                    self.audit = None;
+                    // Show the current route with the new registry
+                    self.show(ctx, self.route.clone());
+                } else if let Some((seq, asset_id)) = self.pending_asset.take() {
+                    ctx.link().send_message(Msg::ShowAsset(seq, asset_id));
+                }
+                true
+            }
+            Msg::SetFormat(format) => {
+                LocalStorage::set(FORMAT_KEY, format.to_string()).ok();
+                self.store.swap(remote_sources(format));
+                false
+            }
+            Msg::Navigate(route) => {
+                self.route = route.clone();
+                self.write_url(true);
+                self.show(ctx, route);
+                true
+            }
+            Msg::Route => {
+                let url = gloo::utils::window().location().hash().unwrap_or_default();
+                if url == self.url {
+                    return false;
+                }
+                self.url = url;
+                let (route, params) = Route::parse(&self.url);
+                if let Some(columns) = params.get(COLUMNS_KEY) {
+                    self.columns = columns.split(',').filter_map(|x| x.parse().ok()).collect();
+                }
+                if let Some(sort) = params.get(SORT_KEY) {
+                    self.sort = sort.parse().ok();
+                }
+                self.page = Self::page_param(&params);
+                if route != self.route {
+                    self.route = route.clone();
+                    self.show(ctx, route);
+                }
+                self.queue_markets(ctx);
+                true
+            }
             Msg::SetMarkdownFetchState(fetch_state) => {
                 self.state = fetch_state;
+                self.queue_markets(ctx);
+                true
+            }
+            Msg::SetCurrency(currency) => {
+                self.currency = currency;
+                LocalStorage::set(CURRENCY_KEY, currency).ok();
+                // Market caps are quoted in the reference currency, fetch them again
+                self.markets.clear();
+                self.queue_markets(ctx);
+                if let FetchState::Single(detail) = &self.state {
+                    let asset_id = detail.asset.asset_id;
+                    ctx.link().send_future(async move {
+                        let price = registry
+                            .price(oracle.as_ref(), asset_id, currency)
+                            .await
+                            .ok();
+                        Msg::SetPrice(asset_id, price)
+                    });
+                }
                 true
             }
-            Msg::GetVisibleAssets() => {
-                let ids: Vec<elements::AssetId> = LocalStorage::get("ids").unwrap();
+            Msg::SetPrice(asset_id, price) => match &mut self.state {
+                FetchState::Single(detail) if detail.asset.asset_id == asset_id => {
+                    detail.price = price;
+                    true
+                }
+                _ => false,
+            },
+            Msg::FetchMarkets => {
+                if self.market_queue.is_empty() {
+                    self.fetching_markets = false;
+                    return false;
+                }
+                let delay = self.fetching_markets;
+                self.fetching_markets = true;
+                let batch: Vec<AssetId> = self
+                    .market_queue
+                    .drain(..MARKET_BATCH_SIZE.min(self.market_queue.len()))
+                    .collect();
                 ctx.link().send_future(async move {
-                    match REGISTRY.query_by_ids(ids).await {
-                        Ok(ass) => Msg::SetMarkdownFetchState(FetchState::Success(ass)),
-                        Err(_) => Msg::SetMarkdownFetchState(FetchState::Failed()),
+                    if delay {
+                        TimeoutFuture::new(MARKET_BATCH_DELAY_MS).await;
                     }
+                    let markets = registry
+                        .markets(
+                            backend.as_ref(),
+                            oracle.as_ref(),
+                            &batch,
+                            currency,
+                            MARKET_CONCURRENCY,
+                        )
+                        .await;
+                    Msg::SetMarkets(markets, currency)
                 });
+                false
+            }
+            Msg::SetMarkets(markets, currency) => {
+                ctx.link().send_message(Msg::FetchMarkets);
+                // Drop the markets quoted in a currency no longer selected
+                if currency != self.currency {
+                    return false;
+                }
+                self.markets.extend(markets);
+                true
+            }
+            Msg::ToggleColumn(column) => {
+                match self.columns.iter().position(|x| *x == column) {
+                    Some(index) => {
+                        self.columns.remove(index);
+                    }
+                    None => {
+                        self.columns.push(column);
+                        self.columns
+                            .sort_by_key(|x| Column::ALL.iter().position(|y| y == x));
+                    }
+                }
+                LocalStorage::set(COLUMNS_KEY, &self.columns).ok();
+                self.write_url(false);
+                self.queue_markets(ctx);
+                true
+            }
+            Msg::SortBy(column) => {
+                let sort = Sort::toggle(self.sort, column);
+                self.sort = Some(sort);
+                self.page = 0;
+                LocalStorage::set(SORT_KEY, sort).ok();
+                self.write_url(false);
+                self.queue_markets(ctx);
+                true
+            }
+            Msg::SetPage(page) => {
+                self.page = page;
+                self.write_url(false);
+                self.queue_markets(ctx);
+                true
+            }
+            Msg::StartAudit => {
+                self.audit = Some(Audit::default());
                 ctx.link()
-                    .send_message(Msg::SetMarkdownFetchState(FetchState::Fetching));
+                    .send_message(Msg::AuditBatch(self.store.generation));
+                true
+            }
+            Msg::AuditBatch(generation) => {
+                // Stop the audits of replaced registries
+                let audit = match self.audit.as_mut() {
+                    Some(audit) if generation == self.store.generation => audit,
+                    _ => return false,
+                };
+                let assets = &self.store.registry.assets;
+                let end = assets.len().min(audit.total + AUDIT_BATCH_SIZE);
+                (audit.total..end)
+                    .filter_map(|i| assets.get_index(i))
+                    .for_each(|(_, asset)| audit.add(asset));
+                if audit.total < assets.len() {
+                    ctx.link().send_future(async move {
+                        TimeoutFuture::new(0).await;
+                        Msg::AuditBatch(generation)
+                    });
+                }
+                true
+            }
+            Msg::Download(file) => {
+                let (name, mime, content) = match (file, &self.state, self.audit.as_ref()) {
+                    (Download::Assets(format), FetchState::Success(assets), _) => {
+                        let export = format.export(&self.sorted(assets), now());
+                        (format.file_name(), format.mime(), export)
+                    }
+                    (Download::AuditJson, _, Some(audit)) => {
+                        ("audit.json", "application/json", audit.to_json())
+                    }
+                    (Download::AuditCsv, _, Some(audit)) => {
+                        ("audit.csv", "text/csv", Ok(audit.to_csv()))
+                    }
+                    _ => return false,
+                };
+                self.download_error = content
+                    .map_err(|err| err.to_string())
+                    .and_then(|content| download(name, mime, &content))
+                    .err()
+                    .map(|err| format!("{} not downloaded: {}", name, err));
+                true
+            }
+            Msg::LoadImports => {
+                let imports: Vec<ImportSource> =
+                    self.imports.iter().filter(|x| x.enabled).cloned().collect();
+                self.import_seq += 1;
+                let seq = self.import_seq;
+                ctx.link().send_future(async move {
+                    let mut parsed = vec![];
+                    let mut errors = IndexMap::new();
+                    for import in imports {
+                        let content = match &import.origin {
+                            Origin::Url(url) => ReqwestClient::default().get(url).await,
+                            Origin::File(content) => Ok(content.clone().into_bytes()),
+                        };
+                        match content
+                            .and_then(|x| Import::parse(&import.name, import.precedence, &x))
+                        {
+                            Ok(x) => parsed.push(x),
+                            Err(err) => {
+                                errors.insert(import.name, err.to_string());
+                            }
+                        }
+                    }
+                    Msg::SetImports(seq, parsed, errors)
+                });
                 false
             }
+            Msg::SetImports(seq, imports, errors) => {
+                if seq != self.import_seq {
+                    return false;
+                }
+                self.import_errors = errors;
+                self.store.set_imports(imports);
+                true
+            }
+            Msg::AddImport(import) => {
+                // Replace the import of the same name
+                self.imports.retain(|x| x.name != import.name);
+                self.imports.push(import);
+                self.import_url.clear();
+                self.save_imports(ctx);
+                true
+            }
+            Msg::ImportError(name, err) => {
+                self.import_errors.insert(name, err);
+                true
+            }
+            Msg::UploadImport(file) => {
+                let file = gloo::file::File::from(file);
+                ctx.link().send_future(async move {
+                    let name = file.name();
+                    match gloo::file::futures::read_as_text(&file).await {
+                        Ok(content) => Msg::AddImport(ImportSource {
+                            name,
+                            origin: Origin::File(content),
+                            precedence: Precedence::default(),
+                            enabled: true,
+                        }),
+                        Err(err) => Msg::ImportError(name, err.to_string()),
+                    }
+                });
+                false
+            }
+            Msg::SetImportUrl(url) => {
+                self.import_url = url;
+                false
+            }
+            Msg::ToggleImport(index) => {
+                if let Some(import) = self.imports.get_mut(index) {
+                    import.enabled = !import.enabled;
+                    self.save_imports(ctx);
+                }
+                true
+            }
+            Msg::SetPrecedence(index, precedence) => {
+                if let Some(import) = self.imports.get_mut(index) {
+                    import.precedence = precedence;
+                    self.save_imports(ctx);
+                }
+                true
+            }
+            Msg::MoveImport(index) => {
+                if index > 0 && index < self.imports.len() {
+                    self.imports.swap(index - 1, index);
+                    self.save_imports(ctx);
+                }
+                true
+            }
+            Msg::RemoveImport(index) => {
+                if index < self.imports.len() {
+                    self.imports.remove(index);
+                    self.save_imports(ctx);
+                }
+                true
+            }
+            Msg::SetPageSize(page_size) => {
+                // Keep the first asset of the page in view
+                self.page = self.page * self.page_size / page_size;
+                self.page_size = page_size;
+                LocalStorage::set(PAGE_SIZE_KEY, page_size).ok();
+                self.write_url(false);
+                self.queue_markets(ctx);
+                true
+            }
             Msg::GetAssets(filter) => {
                 console_dbg!("update");
+                // Supersede the pending search and the queries in flight
+                self.search_seq += 1;
+                self.query_seq += 1;
+                self.highlight = filter.text().unwrap_or_default().to_string();
+                let seq = self.query_seq;
+                let retry = Retry::Assets(filter.clone());
                 ctx.link().send_future(async move {
-                    match REGISTRY.query(filter).await {
+                    match registry.query(filter).await {
                         Ok(ids) => {
                             console_dbg!("query await");
-                            LocalStorage::set("ids", ids.clone()).unwrap();
-                            match REGISTRY.query_by_ids(ids).await {
-                                Ok(ass) => {
-                                    console_dbg!("query_by_ids await");
-                                    Msg::SetMarkdownFetchState(FetchState::Success(ass))
-                                }
-                                Err(_) => Msg::SetMarkdownFetchState(FetchState::Failed()),
-                            }
+                            Msg::SetAssets(seq, FetchState::Success(ids))
                         }
-                        Err(_) => Msg::SetMarkdownFetchState(FetchState::Failed()),
+                        Err(err) => Msg::SetAssets(seq, FetchState::Failed(err, retry)),
                     }
                 });
                 ctx.link()
                     .send_message(Msg::SetMarkdownFetchState(FetchState::Fetching));
                 false
             }
+            Msg::SetAssets(seq, fetch_state) => {
+                if seq != self.query_seq {
+                    return false;
+                }
+                ctx.link()
+                    .send_message(Msg::SetMarkdownFetchState(fetch_state));
+                false
+            }
+            Msg::SetSearch(text) => {
+                self.search = text;
+                self.search_seq += 1;
+                let seq = self.search_seq;
+                ctx.link().send_future(async move {
+                    TimeoutFuture::new(SEARCH_DEBOUNCE_MS).await;
+                    Msg::Search(seq)
+                });
+                false
+            }
+            Msg::Search(seq) => {
+                if seq == self.search_seq {
+                    // A single history entry for the keystrokes of a search
+                    let push = !matches!(self.route, Route::Search(_));
+                    self.route = Route::Search(self.search.clone());
+                    self.page = 0;
+                    self.write_url(push);
+                    self.show(ctx, self.route.clone());
+                }
+                false
+            }
             Msg::GetAsset(id) => {
+                // Supersede the queries in flight
+                self.query_seq += 1;
+                let seq = self.query_seq;
+                let retry = Retry::Asset(id.clone());
+                let asset_id = match elements::AssetId::from_str(&id) {
+                    Ok(asset_id) => asset_id,
+                    Err(_) => {
+                        let err = RegistryError::InvalidAssetId(id);
+                        self.state = FetchState::Failed(err, retry);
+                        return true;
+                    }
+                };
+                // Already fetched since the store was created
+                if self.store.entry(&asset_id).is_some() {
+                    ctx.link().send_message(Msg::ShowAsset(seq, asset_id));
+                    return false;
+                }
                 ctx.link().send_future(async move {
-                    let id = elements::AssetId::from_str(&id).unwrap();
-                    match REGISTRY.query_by_id(id).await {
-                        Ok(ass) => {
-                            let supply: Option<String> = REGISTRY.supply(id).await.ok();
-                            let price: Option<String> = REGISTRY.price(id).await.ok();
-                            let verified = ass
-                                .asset_entry
-                                .as_ref()
-                                .is_some_and(|x| x.verifies().is_ok_and(|x| x));
-                            Msg::SetMarkdownFetchState(FetchState::Single(
-                                ass.clone(),
-                                supply,
-                                price,
-                                verified,
-                            ))
+                    let key = Self::asset_entry_key(asset_id);
+                    let cached = LocalStorage::get::<CachedEntry>(&key)
+                        .ok()
+                        .filter(|x| now().saturating_sub(x.fetched) < ASSET_ENTRY_TTL_SECS);
+                    let upgraded = match cached {
+                        Some(cached) => Ok(cached.entry),
+                        None => {
+                            let upgraded = registry.upgrade(backend.as_ref(), asset_id).await;
+                            if let Ok(entry) = &upgraded {
+                                let cached = CachedEntry {
+                                    fetched: now(),
+                                    entry: entry.clone(),
+                                };
+                                LocalStorage::set(&key, cached).ok();
+                            }
+                            upgraded
                         }
-                        Err(err) => Msg::SetMarkdownFetchState(FetchState::Failed()),
+                    };
+                    match upgraded {
+                        Ok(entry) => Msg::SetAssetEntry(seq, asset_id, Some(Box::new(entry))),
+                        Err(RegistryError::NotFound(id)) => Msg::SetAssets(
+                            seq,
+                            FetchState::Failed(RegistryError::NotFound(id), retry),
+                        ),
+                        // Show the minimal entry when the full one can't be fetched
+                        Err(_) => Msg::SetAssetEntry(seq, asset_id, None),
                     }
                 });
                 ctx.link()
                     .send_message(Msg::SetMarkdownFetchState(FetchState::Fetching));
                 false
             }
-            Msg::GetError => {
-                //ctx.link().send_future(async {
-                //match fetch_markdown(INCORRECT_URL).await {
-                //    Ok(md) => Msg::SetMarkdownFetchState(FetchState::Success(md)),
-                //    Err(err) => Msg::SetMarkdownFetchState(FetchState::Failed(err)),
-                //}
-                // });
-                ctx.link()
-                    .send_message(Msg::SetMarkdownFetchState(FetchState::Fetching));
+            Msg::SetAssetEntry(seq, asset_id, entry) => {
+                if seq != self.query_seq {
+                    return false;
+                }
+                match entry {
+                    Some(entry) => {
+                        // Shown once the store has the fetched entry
+                        self.pending_asset = Some((seq, asset_id));
+                        self.store.merge(*entry);
+                    }
+                    None => ctx.link().send_message(Msg::ShowAsset(seq, asset_id)),
+                }
+                false
+            }
+            Msg::ShowAsset(seq, asset_id) => {
+                if seq != self.query_seq {
+                    return false;
+                }
+                let ass = match self.store.asset(&asset_id) {
+                    Some(ass) => ass,
+                    None => {
+                        let err = RegistryError::NotFound(asset_id);
+                        self.state = FetchState::Failed(err, Retry::Asset(asset_id.to_string()));
+                        return true;
+                    }
+                };
+                ctx.link().send_future(async move {
+                    let entry = ass.asset_entry.as_ref();
+                    let supply = registry.supply(backend.as_ref(), ass.asset_id);
+                    let price = registry.price(oracle.as_ref(), ass.asset_id, currency);
+                    let domain_proof = async {
+                        match entry {
+                            Some(entry) => Some(
+                                ProofVerifier::new(ReqwestClient::default())
+                                    .verify(entry)
+                                    .await,
+                            ),
+                            None => None,
+                        }
+                    };
+                    let issuance = async {
+                        match entry {
+                            Some(entry) if entry.issuance_txin.is_some() => {
+                                fetch_and_verify_issuance(backend.as_ref(), entry)
+                                    .await
+                                    .ok()
+                            }
+                            _ => None,
+                        }
+                    };
+                    let (supply, price, domain_proof, issuance) =
+                        futures::join!(supply, price, domain_proof, issuance);
+                    Msg::SetAssets(
+                        seq,
+                        FetchState::Single(Box::new(AssetDetail {
+                            asset: ass.clone(),
+                            supply: supply.ok(),
+                            price: price.ok(),
+                            report: entry.map(|x| x.verify()),
+                            domain_proof,
+                            issuance,
+                        })),
+                    )
+                });
                 false
             }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let body = match &self.state {
-            FetchState::NotFetching => html! {"" },
-            FetchState::Fetching => html! {"Fetching" },
-            FetchState::Success(data) => self.view_list(ctx, data.into()),
-            FetchState::Single(asset, supply, price, verified) => self.view_dialog(
-                ctx,
-                asset.clone(),
-                supply.clone(),
-                price.clone(),
-                verified.clone(),
-            ),
-            FetchState::Failed() => html! {"error"},
+        let body = match &self.store.state {
+            LoadState::Failed(err) if !self.store.is_loaded() => {
+                self.view_error(ctx, err, &Retry::Registry)
+            }
+            _ if !self.store.is_loaded() => html! { "Loading registry" },
+            _ => self.view_body(ctx),
         };
         console_dbg!("body");
         html! {
@@ -169,13 +946,173 @@ impl Component for App {
     }
 }
 impl App {
-    const fn is_alphanumeric(key_code: u32) -> bool {
-        (key_code >= 48 && key_code <= 57)
-            || (key_code >= 65 && key_code <= 90)
-            || (key_code >= 97 && key_code <= 122)
+    fn view_body(&self, ctx: &Context<Self>) -> Html {
+        match self.route {
+            Route::Audit => return self.view_audit(ctx),
+            Route::Sources => return self.view_sources(ctx),
+            _ => {}
+        }
+        match &self.state {
+            FetchState::NotFetching => html! {"" },
+            FetchState::Fetching => html! {"Fetching" },
+            FetchState::Success(data) => self.view_list(ctx, data),
+            FetchState::Single(detail) => self.view_dialog(ctx, detail),
+            FetchState::Failed(err, retry) => self.view_error(ctx, err, retry),
+        }
+    }
+
+    /// The LocalStorage key caching the full entry of an asset.
+    fn asset_entry_key(asset_id: AssetId) -> String {
+        format!("asset_entry/{}", asset_id)
     }
 
-    fn view_header(&self, ctx: &Context<Self>) -> Html {
+    /// The page of the `page` route parameter, from 1, as index from 0.
+    fn page_param(params: &IndexMap<String, String>) -> usize {
+        params
+            .get(PAGE_PARAM)
+            .and_then(|x| x.parse::<usize>().ok())
+            .map_or(0, |x| x.saturating_sub(1))
+    }
+
+    /// Persist the imports and merge them again into the registry. When the
+    /// uploaded files don't fit in the local storage only the urls are
+    /// persisted.
+    fn save_imports(&mut self, ctx: &Context<Self>) {
+        self.save_error = LocalStorage::set(IMPORTS_KEY, &self.imports)
+            .err()
+            .map(|err| {
+                let urls: Vec<&ImportSource> = self
+                    .imports
+                    .iter()
+                    .filter(|x| matches!(x.origin, Origin::Url(_)))
+                    .collect();
+                LocalStorage::set(IMPORTS_KEY, urls).ok();
+                format!(
+                    "The uploaded files couldn't be saved and will be lost on reload: {}",
+                    err
+                )
+            });
+        ctx.link().send_message(Msg::LoadImports);
+    }
+
+    /// Show the view of the route.
+    fn show(&mut self, ctx: &Context<Self>, route: Route) {
+        match route {
+            Route::Asset(id) => ctx.link().send_message(Msg::GetAsset(id)),
+            Route::Filter(name) => {
+                self.list_route = Route::Filter(name.clone());
+                match Filter::preset(&name) {
+                    Some(filter) => {
+                        // Show the query of the filter, to refine it in the search box
+                        self.search = filter.to_string();
+                        ctx.link().send_message(Msg::GetAssets(filter));
+                    }
+                    None => {
+                        let err = RegistryError::InvalidQuery(format!("unknown filter {}", name));
+                        self.state = FetchState::Failed(err, Retry::Assets(Filter::main()));
+                    }
+                }
+            }
+            Route::Sources => {}
+            Route::Audit => {
+                if self.audit.is_none() {
+                    ctx.link().send_message(Msg::StartAudit);
+                }
+            }
+            Route::Search(query) => {
+                self.list_route = Route::Search(query.clone());
+                // Search the text as is when it isn't a valid query
+                let filter = query
+                    .parse()
+                    .unwrap_or_else(|_| Filter::Text(query.clone()));
+                self.search = query;
+                ctx.link().send_message(Msg::GetAssets(filter));
+            }
+        }
+    }
+
+    /// Reflect the route, and the columns, sort and page of list routes, in
+    /// the url, as a new entry of the browser history when `push`.
+    fn write_url(&mut self, push: bool) {
+        let mut url = self.route.path();
+        if self.route.is_list() {
+            let columns: Vec<String> = self.columns.iter().map(|x| x.to_string()).collect();
+            let mut params = vec![format!("{}={}", COLUMNS_KEY, encode(&columns.join(",")))];
+            if let Some(sort) = self.sort {
+                params.push(format!("{}={}", SORT_KEY, encode(&sort.to_string())));
+            }
+            if self.page > 0 {
+                params.push(format!("{}={}", PAGE_PARAM, self.page + 1));
+            }
+            let separator = if url.contains('?') { '&' } else { '?' };
+            url = format!("{}{}{}", url, separator, params.join("&"));
+        }
+        if let Ok(history) = gloo::utils::window().history() {
+            if push {
+                history
+                    .push_state_with_url(&JsValue::NULL, "", Some(&url))
+                    .ok();
+            } else {
+                history
+                    .replace_state_with_url(&JsValue::NULL, "", Some(&url))
+                    .ok();
+            }
+        }
+        self.url = url;
+    }
+
+    /// The listed assets in the order of the current sort.
+    fn sorted(&self, ids: &[AssetId]) -> Vec<&Asset> {
+        let registry = &self.store.registry;
+        let mut assets: Vec<&Asset> = ids.iter().filter_map(|x| registry.assets.get(x)).collect();
+        if let Some(sort) = self.sort {
+            sort.sort(&mut assets, &self.markets);
+        }
+        assets
+    }
+
+    /// The current page, within the pages of `total` assets.
+    fn current_page(&self, total: usize) -> usize {
+        self.page.min(total.div_ceil(self.page_size).max(1) - 1)
+    }
+
+    /// The assets of the current page, among the sorted ones.
+    fn paginate<'a>(&self, assets: &'a [&'a Asset]) -> &'a [&'a Asset] {
+        let start = self.current_page(assets.len()) * self.page_size;
+        let end = (start + self.page_size).min(assets.len());
+        &assets[start..end]
+    }
+
+    /// Queue the assets without market data that can be priced, to sort by
+    /// market cap across the whole list, and the assets of the current page
+    /// when the supply is shown, and start fetching their market data.
+    fn queue_markets(&mut self, ctx: &Context<Self>) {
+        if let FetchState::Success(assets) = &self.state {
+            let sorted = self.sorted(assets);
+            let page = if self.columns.contains(&Column::Supply) {
+                self.paginate(&sorted)
+            } else {
+                &[]
+            };
+            let priced = sorted
+                .iter()
+                .copied()
+                .filter(|x| x.metadata.as_ref().is_some_and(|x| x.is_priced()));
+            let queue: IndexSet<AssetId> = page
+                .iter()
+                .copied()
+                .chain(priced)
+                .filter(|x| !self.markets.contains_key(&x.asset_id))
+                .map(|x| x.asset_id)
+                .collect();
+            self.market_queue = queue.into_iter().collect();
+            if !self.fetching_markets {
+                ctx.link().send_message(Msg::FetchMarkets);
+            }
+        }
+    }
+
+    fn view_header(&self, _ctx: &Context<Self>) -> Html {
         let github_link = "https://github.com/lvaccaro/enciclopedia";
         html! {
             <header class="sticky">
@@ -195,97 +1132,496 @@ impl App {
     }
 
     fn view_filters(&self, ctx: &Context<Self>) -> Html {
+        let navigate = |name: &'static str| {
+            ctx.link()
+                .callback(move |_| Msg::Navigate(Route::Filter(name.into())))
+        };
         html! {
             <section class="topic">
                 <section class="showcase">
                     <section class=""><h3 class="title"> </h3>
 
                     <div class="item">
-                            <button class="nes-btn is-primary" onclick={ctx.link().callback(|_| Msg::GetAssets(Filter::Main))}>
+                            <button class="nes-btn is-primary" onclick={navigate("main")}>
                                 { "Main" }
                             </button> { " " }
-                            <button class="nes-btn is-success" onclick={ctx.link().callback(|_| Msg::GetAssets(Filter::Amp))}>
+                            <button class="nes-btn is-success" onclick={navigate("amp")}>
                                 { "Amp" }
                             </button> { " " }
-                            <button class="nes-btn is-warning" onclick={ctx.link().callback(|_| Msg::GetAssets(Filter::Stablecoins))}>
+                            <button class="nes-btn is-warning" onclick={navigate("stablecoins")}>
                                 { "Stablecoins" }
                             </button> { " " }
-                            <button class="nes-btn is-error" onclick={ctx.link().callback(|_| Msg::GetAssets(Filter::Iconed))}>
+                            <button class="nes-btn is-error" onclick={navigate("iconed")}>
                                 { "Iconed" }
                             </button> { " " }
-                            <button class="nes-btn" onclick={ctx.link().callback(|_| Msg::GetAssets(Filter::All))}>
+                            <button class="nes-btn is-error" onclick={navigate("meme")}>
+                                { "Meme" }
+                            </button> { " " }
+                            <button class="nes-btn" onclick={navigate("all")}>
                                 { "All" }
-                            </button>
+                            </button> { " " }
+                            <button class="nes-btn" onclick={ctx.link().callback(|_| Msg::Navigate(Route::Audit))}>
+                                { "Audit" }
+                            </button> { " " }
+                            <button class="nes-btn" onclick={ctx.link().callback(|_| Msg::Navigate(Route::Sources))}>
+                                { "Sources" }
+                            </button> { " " }
+                            { self.view_currency(ctx) } { " " }
+                            { self.view_format(ctx) }
                     </div>
+                    { self.view_input(ctx) }
                     </section>
                 </section>
                 </section>
         }
     }
 
-    fn view_input(&self, ctx: &Scope<Self>) -> Html {
-        let onkeypress = ctx.batch_callback(|e: KeyboardEvent| {
-            console_dbg!(e.key_code());
-            if Self::is_alphanumeric(e.key_code()) {
-                let input: InputElement = e.target_unchecked_into();
-                let value = input.value();
-                Some(Msg::GetAssets(Filter::Text(value)))
-            } else {
-                None
-            }
+    fn view_currency(&self, ctx: &Context<Self>) -> Html {
+        let onchange = ctx.link().batch_callback(|e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            Currency::from_str(&select.value())
+                .ok()
+                .map(Msg::SetCurrency)
+        });
+        html! {
+            <div class="nes-select" style="display: inline-block; width: auto;">
+                <select {onchange}>
+                { for Currency::ALL.iter().map(|currency| html! {
+                    <option value={ currency.to_string() } selected={ *currency == self.currency }>
+                        { currency.to_string() }
+                    </option>
+                }) }
+                </select>
+            </div>
+        }
+    }
+
+    /// The imported metadata files, with the conflicts of their merge.
+    fn view_sources(&self, ctx: &Context<Self>) -> Html {
+        let url = self.import_url.clone();
+        let onclick_add = ctx.link().batch_callback(move |_| {
+            let url = url.trim();
+            (!url.is_empty()).then(|| {
+                Msg::AddImport(ImportSource {
+                    name: url.to_string(),
+                    origin: Origin::Url(url.to_string()),
+                    precedence: Precedence::default(),
+                    enabled: true,
+                })
+            })
+        });
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let input: InputElement = e.target_unchecked_into();
+            Msg::SetImportUrl(input.value())
+        });
+        let onchange_file = ctx.link().batch_callback(|e: Event| {
+            let input: InputElement = e.target_unchecked_into();
+            input.files().and_then(|x| x.get(0)).map(Msg::UploadImport)
+        });
+        let back = self.list_route.clone();
+        let onclick_back = ctx
+            .link()
+            .callback(move |_: MouseEvent| Msg::Navigate(back.clone()));
+        let conflicts = &self.store.conflicts;
+        html! {
+            <section class="nes-container with-title topic">
+                <p class="title"> { "Metadata sources" } </p>
+                <p> { "Metadata files and token lists merged, in order, into the bundled metadata." } </p>
+                <table class="nes-table is-bordered">
+                <tbody>
+                { for self.imports.iter().enumerate().map(|(index, import)| {
+                    let onclick_toggle = ctx.link().callback(move |_| Msg::ToggleImport(index));
+                    let onclick_up = ctx.link().callback(move |_| Msg::MoveImport(index));
+                    let onclick_remove = ctx.link().callback(move |_| Msg::RemoveImport(index));
+                    let onchange = ctx.link().batch_callback(move |e: Event| {
+                        let select: HtmlSelectElement = e.target_unchecked_into();
+                        Precedence::from_str(&select.value())
+                            .ok()
+                            .map(|x| Msg::SetPrecedence(index, x))
+                    });
+                    html! {
+                        <tr>
+                        <td>
+                            <label>
+                                <input type="checkbox" class="nes-checkbox is-dark"
+                                    checked={ import.enabled } onclick={onclick_toggle}/>
+                                <span style="overflow-wrap: anywhere;"> { import.name.clone() } </span>
+                            </label>
+                            <p class="nes-text is-error"> { self.import_errors.get(&import.name).cloned().unwrap_or_default() } </p>
+                        </td>
+                        <td>
+                            <div class="nes-select">
+                                <select {onchange}>
+                                { for Precedence::ALL.iter().map(|precedence| html! {
+                                    <option value={ precedence.to_string() } selected={ *precedence == import.precedence }>
+                                        { precedence.to_string() }
+                                    </option>
+                                }) }
+                                </select>
+                            </div>
+                        </td>
+                        <td>
+                            <button class="nes-btn" onclick={onclick_up} disabled={ index == 0 }>{ "^" }</button> { " " }
+                            <button class="nes-btn is-error" onclick={onclick_remove}>{ "x" }</button>
+                        </td>
+                        </tr>
+                    }
+                }) }
+                </tbody>
+                </table>
+                <div class="nes-field">
+                    <label for="import_url"> { "Import from url" } </label>
+                    <input type="text" id="import_url" class="nes-input"
+                        placeholder="https://example.com/tokenlist.json"
+                        value={ self.import_url.clone() } {oninput}/>
+                </div>
+                <button class="nes-btn is-primary" onclick={onclick_add}>{ "Add" }</button> { " " }
+                <label class="nes-btn">
+                    <span> { "Upload file" } </span>
+                    <input type="file" accept=".json,application/json" style="display: none;" onchange={onchange_file}/>
+                </label>
+                <p class="nes-text is-error">
+                { self.save_error.clone().map(|x| html! { <>{ x }<br/></> }) }
+                { for self.import_errors.iter()
+                    .filter(|(name, _)| !self.imports.iter().any(|x| &x.name == *name))
+                    .map(|(name, err)| html! { <>{ format!("{}: {}", name, err) }<br/></> }) }
+                </p>
+                <p> { format!("{} conflicts", conflicts.len()) } </p>
+                <table class="nes-table is-bordered" hidden={ conflicts.is_empty() }>
+                <tbody>
+                { for conflicts.iter().map(|conflict| html! {
+                    <tr>
+                    <td><a href={ Route::Asset(conflict.asset_id.to_string()).path() }>
+                        { self.store.registry.assets.get(&conflict.asset_id)
+                            .and_then(|x| x.ticker())
+                            .map_or_else(|| conflict.asset_id.to_string(), |x| x.to_string()) }
+                    </a></td>
+                    <td> { conflict.field } </td>
+                    <td style="overflow-wrap: anywhere;"> { conflict.current.clone() } </td>
+                    <td style="overflow-wrap: anywhere;"> { format!("{} in {}", conflict.incoming, conflict.source) } </td>
+                    <td> { if conflict.replaced { "replaced" } else { "kept" } } </td>
+                    </tr>
+                }) }
+                </tbody>
+                </table>
+                <button class="nes-btn" onclick={onclick_back}>{ "Back" }</button>
+            </section>
+        }
+    }
+
+    /// The progress and the outcome of the audit of the registry.
+    fn view_audit(&self, ctx: &Context<Self>) -> Html {
+        let audit = match self.audit.as_ref() {
+            Some(audit) => audit,
+            None => return html! {},
+        };
+        let total = self.store.registry.assets.len();
+        let onclick = ctx.link().callback(|_| Msg::StartAudit);
+        let download = |file: Download| ctx.link().callback(move |_| Msg::Download(file));
+        let back = self.list_route.clone();
+        let onclick_back = ctx
+            .link()
+            .callback(move |_: MouseEvent| Msg::Navigate(back.clone()));
+        let done = audit.total >= total;
+        html! {
+            <section class="nes-container with-title topic">
+                <p class="title"> { "Audit" } </p>
+                <progress class="nes-progress is-primary" value={ audit.total.to_string() } max={ total.to_string() }></progress>
+                <p> { format!("{} of {} assets verified", audit.total, total) } </p>
+                <p hidden={ self.store.sources.format == Format::Full }>
+                    { "The minimal index has no contracts: switch to the full format to verify the assets." }
+                </p>
+                <table class="nes-table is-bordered">
+                <tbody>
+                    <tr><td> { "Verified" } </td><td> { audit.verified } </td></tr>
+                    <tr><td> { "Failed" } </td><td> { audit.failures.len() } </td></tr>
+                    <tr><td> { "Missing contract" } </td><td> { audit.missing_contract.len() } </td></tr>
+                    <tr><td> { "Missing issuance prevout" } </td><td> { audit.missing_prevout.len() } </td></tr>
+                </tbody>
+                </table>
+                <table class="nes-table is-bordered" hidden={ audit.failures.is_empty() }>
+                <tbody>
+                { for audit.failures.iter().map(|failure| html! {
+                    <tr>
+                    <td><a href={ Route::Asset(failure.asset_id.to_string()).path() }>
+                        { failure.ticker.clone().unwrap_or_else(|| failure.asset_id.to_string()) }
+                    </a></td>
+                    <td style="overflow-wrap: anywhere;">
+                        { for failure.reasons.iter().map(|reason| html! { <>{ reason }<br/></> }) }
+                    </td>
+                    </tr>
+                }) }
+                </tbody>
+                </table>
+                <button class="nes-btn" onclick={onclick_back}>{ "Back" }</button> { " " }
+                <button class="nes-btn is-primary" {onclick} disabled={ !done }>{ "Run again" }</button> { " " }
+                <button class="nes-btn" onclick={download(Download::AuditJson)} disabled={ !done }>{ "JSON" }</button> { " " }
+                <button class="nes-btn" onclick={download(Download::AuditCsv)} disabled={ !done }>{ "CSV" }</button>
+                <p class="nes-text is-error" hidden={ self.download_error.is_none() }>
+                    { self.download_error.clone().unwrap_or_default() }
+                </p>
+            </section>
+        }
+    }
+
+    /// The format of the registry, its loading state and a button to reload
+    /// it.
+    fn view_format(&self, ctx: &Context<Self>) -> Html {
+        let onchange = ctx.link().batch_callback(|e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            Format::from_str(&select.value()).ok().map(Msg::SetFormat)
+        });
+        let onclick = ctx.link().callback(|_| Msg::LoadRegistry);
+        let (class, status) = match &self.store.state {
+            LoadState::Loading => ("nes-text is-disabled", "loading".to_string()),
+            LoadState::Loaded => (
+                "nes-text is-success",
+                format!("{} assets", self.store.registry.assets.len()),
+            ),
+            LoadState::Failed(err) => ("nes-text is-error", err.to_string()),
+        };
+        html! {
+            <>
+            <div class="nes-select" style="display: inline-block; width: auto;">
+                <select {onchange}>
+                { for Format::ALL.iter().map(|format| html! {
+                    <option value={ format.to_string() } selected={ *format == self.store.sources.format }>
+                        { format.to_string() }
+                    </option>
+                }) }
+                </select>
+            </div> { " " }
+            <button class="nes-btn" {onclick} disabled={ matches!(self.store.state, LoadState::Loading) }>
+                { "Reload" }
+            </button> { " " }
+            <span class={class}> { status } </span>
+            </>
+        }
+    }
+
+    fn view_error(&self, ctx: &Context<Self>, err: &RegistryError, retry: &Retry) -> Html {
+        let retry = retry.clone();
+        let onclick = ctx.link().callback(move |_| Msg::from(retry.clone()));
+        html! {
+            <section class="nes-container is-rounded topic">
+                <p class="nes-text is-error"> { err.to_string() } </p>
+                <button class="nes-btn is-warning" {onclick}>{ "Retry" }</button>
+            </section>
+        }
+    }
+
+    fn view_input(&self, ctx: &Context<Self>) -> Html {
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let input: InputElement = e.target_unchecked_into();
+            Msg::SetSearch(input.value())
         });
         html! {
             <div class="nes-field">
             <label for="name_field"> { "Search by" } </label>
             <input type="text" id="name_field" class="nes-input"
-            {onkeypress}
+            placeholder="usdt, amp:true domain:*.io ticker:L-*"
+            value={ self.search.clone() }
+            {oninput}
             />
             </div>
         }
     }
 
-    fn view_list(&self, ctx: &Context<Self>, assets: &Vec<&Asset>) -> Html {
+    /// The text with the first match of the search highlighted.
+    fn view_highlight(&self, text: &str) -> Html {
+        match search::find(text, &self.highlight) {
+            Some(range) => html! {
+                <>
+                { &text[..range.start] }
+                <mark> { &text[range.clone()] } </mark>
+                { &text[range.end..] }
+                </>
+            },
+            None => html! { text },
+        }
+    }
+
+    fn view_columns(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="item">
+            { for Column::ALL.iter().map(|column| {
+                let column = *column;
+                let onclick = ctx.link().callback(move |_| Msg::ToggleColumn(column));
+                html! {
+                    <label>
+                        <input type="checkbox" class="nes-checkbox is-dark"
+                            checked={ self.columns.contains(&column) } {onclick}/>
+                        <span> { column.title() } </span>
+                    </label>
+                }
+            }) }
+            </div>
+        }
+    }
+
+    fn view_pager(&self, ctx: &Context<Self>, total: usize) -> Html {
+        let pages = total.div_ceil(self.page_size).max(1);
+        let page = self.current_page(total);
+        let first = (page * self.page_size + 1).min(total);
+        let last = ((page + 1) * self.page_size).min(total);
+        let previous = ctx
+            .link()
+            .callback(move |_| Msg::SetPage(page.saturating_sub(1)));
+        let next = ctx.link().callback(move |_| Msg::SetPage(page + 1));
+        let onchange = ctx.link().batch_callback(|e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            select
+                .value()
+                .parse()
+                .ok()
+                .filter(|x| PAGE_SIZES.contains(x))
+                .map(Msg::SetPageSize)
+        });
+        let class = |enabled: bool| {
+            if enabled {
+                "nes-btn"
+            } else {
+                "nes-btn is-disabled"
+            }
+        };
+        html! {
+            <div class="item">
+                <button class={ class(page > 0) } disabled={ page == 0 } onclick={previous}>{ "<" }</button>
+                { " " } { format!("{}-{} of {}", first, last, total) } { " " }
+                <button class={ class(page + 1 < pages) } disabled={ page + 1 >= pages } onclick={next}>{ ">" }</button>
+                { " " }
+                <div class="nes-select" style="display: inline-block; width: auto;">
+                    <select {onchange}>
+                    { for PAGE_SIZES.iter().map(|size| html! {
+                        <option value={ size.to_string() } selected={ *size == self.page_size }>
+                            { format!("{} per page", size) }
+                        </option>
+                    }) }
+                    </select>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_list(&self, ctx: &Context<Self>, assets: &[AssetId]) -> Html {
+        let sorted = self.sorted(assets);
+        let page = self.paginate(&sorted);
         html! {
 
             <section class="topic">
+            { self.view_columns(ctx) }
+            { self.view_pager(ctx, sorted.len()) }
             <table class="nes-table nes-table-responsive is-bordered">
+            <thead>
+                <tr>
+                <th></th>
+                { for self.columns.iter().map(|column| {
+                    let column = *column;
+                    let onclick = ctx.link().callback(move |_| Msg::SortBy(column));
+                    let arrow = match self.sort {
+                        Some(sort) if sort.column == column && sort.order == Order::Asc => " ^",
+                        Some(sort) if sort.column == column => " v",
+                        _ => "",
+                    };
+                    html! {
+                        <th style="cursor: pointer;" {onclick}> { column.title() } { arrow } </th>
+                    }
+                }) }
+                <th></th>
+                <th></th>
+                </tr>
+            </thead>
             <tbody>
-            { for assets.iter().map(|x| self.view_item(ctx, x)) }
+            { for page.iter().map(|x| self.view_item(ctx, x)) }
             </tbody>
             </table>
+            { self.view_pager(ctx, sorted.len()) }
+            { self.view_export(ctx) }
             </section>
         }
     }
 
-    fn view_dialog(
-        &self,
-        ctx: &Context<Self>,
-        asset: Asset,
-        supply: Option<String>,
-        price: Option<String>,
-        verified: bool,
-    ) -> Html {
+    /// The buttons downloading the assets of the list.
+    fn view_export(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="item">
+            { "Export " }
+            { for ExportFormat::ALL.iter().map(|format| {
+                let format = *format;
+                let onclick = ctx.link().callback(move |_| Msg::Download(Download::Assets(format)));
+                let title = match format {
+                    ExportFormat::Csv => "CSV",
+                    ExportFormat::Json => "JSON",
+                    ExportFormat::TokenList => "Token list",
+                };
+                html! {
+                    <>
+                    <button class="nes-btn" {onclick}> { title } </button> { " " }
+                    </>
+                }
+            }) }
+            <p class="nes-text is-error" hidden={ self.download_error.is_none() }>
+                { self.download_error.clone().unwrap_or_default() }
+            </p>
+            </div>
+        }
+    }
+
+    fn view_cell(&self, column: Column, asset: &Asset) -> Html {
+        let market = self.markets.get(&asset.asset_id);
+        let text = |text: Option<&str>| {
+            html! {
+                <th> { self.view_highlight(text.unwrap_or_default()) } </th>
+            }
+        };
+        let value = |value: Option<String>| {
+            html! {
+                <th> { value.unwrap_or_default() } </th>
+            }
+        };
+        match column {
+            Column::AssetId => html! {
+                <th style="overflow-wrap: anywhere;"> { self.view_highlight(&asset.asset_id.to_string()) } </th>
+            },
+            Column::Domain => text(asset.domain()),
+            Column::Ticker => text(asset.ticker()),
+            Column::Name => text(asset.name()),
+            Column::Precision => value(asset.asset_entry.as_ref().map(|x| x.precision.to_string())),
+            Column::Weight => value(asset.weight().map(|x| x.to_string())),
+            Column::Supply => value(market.and_then(|x| x.supply).map(|x| x.to_string())),
+            Column::Price => value(market.and_then(|x| x.price).map(|x| x.to_string())),
+            Column::MarketCap => value(market.and_then(|x| x.market_cap()).map(|x| x.to_string())),
+            Column::Verified => html! {
+                <th><span class="nes-text is-primary" hidden={ !asset.is_verified() }>{ "verified" }</span></th>
+            },
+        }
+    }
+
+    fn view_dialog(&self, ctx: &Context<Self>, detail: &AssetDetail) -> Html {
+        let asset = &detail.asset;
+        let supply = detail.supply.map(|x| x.to_string());
+        let price = detail.price.map(|x| x.to_string());
+        let market_cap = detail
+            .supply
+            .zip(detail.price)
+            .map(|(supply, price)| supply.value(&price).to_string());
+        let report = detail.report.as_ref();
+        let verified = report.is_some_and(|x| x.verified());
+        let back = self.list_route.clone();
         let onkeypress_cancel = ctx
             .link()
-            .batch_callback(|_: MouseEvent| Some(Msg::GetVisibleAssets()));
-        let onkeypress_validate = ctx
-            .link()
-            .batch_callback(|_: MouseEvent| Some(Msg::GetVisibleAssets()));
+            .callback(move |_: MouseEvent| Msg::Navigate(back.clone()));
         let asset_entry = asset.asset_entry.as_ref();
         let name = asset_entry.map_or("", |a| a.name.as_str());
         let ticker = asset_entry.map_or("", |a| a.ticker.as_ref().map_or("", |t| t.as_str()));
         let domain = asset_entry.map_or("", |a| a.domain().map_or("", |d| d));
-        let esplora = format!(
-            "https://blockstream.info/liquid/asset/{}",
-            asset.asset_id.to_string()
-        );
+        let esplora = format!("https://blockstream.info/liquid/asset/{}", asset.asset_id);
         let sideswap = format!("https://sideswap.io/swap-market/?product={}", ticker);
         let base64 = asset.icon.as_ref();
         let image = format!(
             "data:image/png;base64, {}",
             base64.unwrap_or(&"".to_string())
         );
-        let pair = asset.metadata.as_ref().and_then(|x| x.pair.clone());
         html! {
 
             <div>
@@ -294,6 +1630,14 @@ impl App {
                     <img src={image} class=""/>
                     <span class="nes-text is-error" hidden={ verified }>{ "unverified" }</span>
                     <span class="nes-text is-primary" hidden={ !verified }>{ "verified" }</span>
+                    { match &detail.domain_proof {
+                        Some(Ok(true)) => html! { <span class="nes-text is-success">{ " domain proven" }</span> },
+                        Some(Ok(false)) => html! { <span class="nes-text is-error">{ " domain not proven" }</span> },
+                        Some(Err(err)) => html! {
+                            <span class="nes-text is-warning" title={ err.to_string() }>{ " domain proof unchecked" }</span>
+                        },
+                        None => html! {},
+                    } }
                 </div>
                 <div class="profile">
                     <h4 class="name">{ticker}</h4>
@@ -312,9 +1656,13 @@ impl App {
                 <input type="text" class="nes-input is-warning" value={ supply.unwrap_or("".to_string()) }/>
             </div>
             <div class="nes-field is-inline" hidden={ price.is_none() }>
-                <label for="warning_field"> { "Price " } { pair.unwrap_or("".to_string()) }</label>
+                <label for="warning_field"> { "Price" } </label>
                 <input type="text" class="nes-input is-success" value={ price.unwrap_or("".to_string()) }/>
             </div>
+            <div class="nes-field is-inline" hidden={ market_cap.is_none() }>
+                <label for="warning_field"> { "Market cap" } </label>
+                <input type="text" class="nes-input is-success" value={ market_cap.unwrap_or("".to_string()) }/>
+            </div>
             </section>
 
             <section class="topic">
@@ -333,6 +1681,8 @@ impl App {
                 </div>
             </section>
 
+            { self.view_report(report, detail.issuance.as_ref(), asset.precision()) }
+
             <section class="topic">
                 <menu class="dialog-menu">
                     <button class="nes-btn" onclick={onkeypress_cancel}>{"Back"}</button>
@@ -341,33 +1691,73 @@ impl App {
                     { " " }
                     <a class="nes-btn is-primary" href={ sideswap }>{ "Sideswap" }</a>
                     { " " }
-                    //<button class="nes-btn is-success" onclick={onkeypress_validate}>{"Validate"}</button>
                 </menu>
             </section>
             </div>
         }
     }
+    fn view_report(
+        &self,
+        report: Option<&VerificationReport>,
+        issuance: Option<&IssuanceReport>,
+        precision: u8,
+    ) -> Html {
+        let checks = report
+            .map_or(&[][..], |x| x.checks.as_slice())
+            .iter()
+            .chain(issuance.map_or(&[][..], |x| x.checks.as_slice()));
+        let issued = |amount: Option<IssuedAmount>, precision: u8| match amount {
+            Some(IssuedAmount::Explicit(amount)) => {
+                format!("{} (explicit)", Amount::from_sat(amount, precision))
+            }
+            Some(IssuedAmount::Confidential) => "confidential".to_string(),
+            Some(IssuedAmount::Null) => "none".to_string(),
+            None => "unknown".to_string(),
+        };
+        html! {
+            <section class="nes-container with-title topic">
+                <p class="title"> { "Verification" } </p>
+                <p hidden={ issuance.is_none() }>
+                    { "Issued amount: " } { issued(issuance.and_then(|x| x.amount), precision) }
+                    <br/>
+                    { "Reissuance tokens: " } { issued(issuance.and_then(|x| x.inflation_keys), 0) }
+                </p>
+                <table class="nes-table is-bordered">
+                <tbody>
+                { for checks.map(|check| {
+                    let (class, status) = match check.status {
+                        Status::Pass => ("nes-text is-success", "pass"),
+                        Status::Fail => ("nes-text is-error", "fail"),
+                        Status::Missing => ("nes-text is-disabled", "missing"),
+                    };
+                    html! {
+                        <tr>
+                        <td> { check.name } </td>
+                        <td><span class={class}> { status } </span></td>
+                        <td style="overflow-wrap: anywhere;"> { check.detail.clone().unwrap_or_default() } </td>
+                        </tr>
+                    }
+                }) }
+                </tbody>
+                </table>
+            </section>
+        }
+    }
+
     fn view_item(&self, ctx: &Context<Self>, asset: &Asset) -> Html {
-        let asset_entry = asset.asset_entry.as_ref();
-        let name = asset_entry.map_or("", |a| a.name.as_str());
-        let ticker = asset_entry.map_or("", |a| a.ticker.as_ref().map_or("", |t| t.as_str()));
         let base64 = asset.icon.as_ref();
         let image = format!(
             "data:image/png;base64, {}",
             base64.unwrap_or(&"".to_string())
         );
-        let onkeypress = ctx.link().batch_callback(move |e: MouseEvent| {
-            let target: Option<EventTarget> = e.target();
-            let input = target.and_then(|t| t.dyn_into::<HtmlElement>().ok());
-
-            let id = input.unwrap().id();
-            Some(Msg::GetAsset(id))
-        });
+        let route = Route::Asset(asset.asset_id.to_string());
+        let onkeypress = ctx
+            .link()
+            .callback(move |_: MouseEvent| Msg::Navigate(route.clone()));
         html! {
             <tr>
             <th> <img src={image} class="nes-icon coin is-large"/> </th>
-            <th> { ticker } </th>
-            <th> { name } </th>
+            { for self.columns.iter().map(|column| self.view_cell(*column, asset)) }
             <th>
                 <a class="nes-badge" href="#" hidden={!asset.is_amp()}>
                     <span class="is-success" > { "amp" } </span>
@@ -380,7 +1770,7 @@ impl App {
                 </a>
             </th>
             <th>
-                <button type="button" class="nes-btn is-primary" onclick={ onkeypress } id={ asset.asset_id.to_string() }>//tx.link().callback(|_| Msg::GetAsset(asset.clone()))}>
+                <button type="button" class="nes-btn is-primary" onclick={ onkeypress }>
                 { "<>" }
                 </button>
             </th>
@@ -390,12 +1780,66 @@ impl App {
         }
     }
 }
-use lazy_static::lazy_static;
+/// The current time, in seconds since the unix epoch.
+fn now() -> u64 {
+    let millis = gloo::utils::window()
+        .performance()
+        .map_or(0.0, |x| x.time_origin() + x.now());
+    (millis / 1000.0) as u64
+}
 
-lazy_static! {
-    static ref REGISTRY: Registry = Registry::new();
+/// Let the user save the content as a file with the given name. The content
+/// is passed as a blob, data urls are limited to a few megabytes.
+fn download(name: &str, mime: &str, content: &str) -> Result<(), String> {
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    let parts = Array::of1(&JsValue::from_str(content));
+    let url = Blob::new_with_str_sequence_and_options(&parts, &options)
+        .and_then(|blob| Url::create_object_url_with_blob(&blob))
+        .map_err(|err| err.as_string().unwrap_or_else(|| format!("{:?}", err)))?;
+    let document = gloo::utils::document();
+    if let Ok(link) = document.create_element("a") {
+        link.set_attribute("href", &url).ok();
+        link.set_attribute("download", name).ok();
+        if let Ok(link) = link.dyn_into::<HtmlElement>() {
+            link.click();
+        }
+    }
+    // Released once the browser has started the download
+    wasm_bindgen_futures::spawn_local(async move {
+        TimeoutFuture::new(10_000).await;
+        Url::revoke_object_url(&url).ok();
+    });
+    Ok(())
+}
+
+/// The registry files refreshed nightly, in the given format.
+fn remote_sources(format: Format) -> Sources {
+    let index_url = match format {
+        Format::Minimal => INDEX_MINIMAL_URL,
+        Format::Full => INDEX_FULL_URL,
+    };
+    Sources {
+        format,
+        index: Source::Url(index_url.into()),
+        icons: Source::Url(ICONS_URL.into()),
+        metadata: Source::Url(METADATA_URL.into()),
+    }
+}
+
+#[function_component]
+fn Root() -> Html {
+    let format = LocalStorage::get::<String>(FORMAT_KEY)
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or_default();
+    html! {
+        <RegistryProvider sources={ Rc::new(remote_sources(format)) }>
+            <App />
+        </RegistryProvider>
+    }
 }
 
 fn main() {
-    yew::Renderer::<App>::new().render();
+    yew::Renderer::<Root>::new().render();
 }
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use crate::amount::Amount;
+use crate::price::Quote;
+
+/// Market data of an asset: the circulating supply and the price of one unit
+/// in a reference currency. Either is `None` when it couldn't be fetched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Market {
+    pub supply: Option<Amount>,
+    pub price: Option<Quote>,
+}
+
+impl Market {
+    /// The market capitalization: the circulating supply at the price.
+    pub fn market_cap(&self) -> Option<Quote> {
+        self.supply
+            .zip(self.price)
+            .map(|(supply, price)| supply.value(&price))
+    }
+}
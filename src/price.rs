@@ -0,0 +1,702 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::amount::group_thousands;
+use crate::asset::Metadata;
+use crate::error::RegistryError;
+use crate::http::{HttpClient, MaybeSync, ReqwestClient};
+
+/// A currency assets are priced in. Pairs quoted in USDT are considered as
+/// quoted in USD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    Btc,
+    /// Satoshi, a hundred millionth of a bitcoin.
+    Sat,
+    Usd,
+    Eur,
+}
+
+impl Currency {
+    /// Every supported currency.
+    pub const ALL: [Currency; 4] = [Currency::Usd, Currency::Eur, Currency::Btc, Currency::Sat];
+}
+
+impl FromStr for Currency {
+    type Err = RegistryError;
+
+    /// Parse a currency code, eg. `usd` or `sats`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "BTC" => Ok(Currency::Btc),
+            "SAT" | "SATS" => Ok(Currency::Sat),
+            "USD" | "USDT" => Ok(Currency::Usd),
+            "EUR" => Ok(Currency::Eur),
+            _ => Err(RegistryError::Price(format!("unknown currency {}", s))),
+        }
+    }
+}
+
+impl Display for Currency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            Currency::Btc => "BTC",
+            Currency::Sat => "sats",
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+        };
+        write!(f, "{}", code)
+    }
+}
+
+/// What the price of an asset means, defined in `Metadata::price`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PriceDefinition {
+    /// The trading pair of the metadata prices the asset in `currency`, eg.
+    /// `PEPEUSDT` prices PEPE in USD. When `invert` is set, the pair prices
+    /// `currency` in the asset instead, eg. `BTCUSDT` prices USDt in BTC
+    /// as `1 / price`.
+    Pair {
+        currency: Currency,
+        #[serde(default)]
+        invert: bool,
+    },
+    /// The asset is worth one unit of `currency`, eg. L-BTC is worth one BTC.
+    Peg { currency: Currency },
+}
+
+/// The price of one unit of an asset in a currency.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Quote {
+    pub value: f64,
+    pub currency: Currency,
+}
+
+impl Display for Quote {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let value = match self.currency {
+            Currency::Btc => format!("{:.8}", self.value),
+            Currency::Sat => format!("{:.0}", self.value),
+            _ if self.value < 1.0 => format!("{:.6}", self.value),
+            _ => format!("{:.2}", self.value),
+        };
+        let (int, frac) = value.split_once('.').unwrap_or((&value, ""));
+        let (sign, int) = int.strip_prefix('-').map_or(("", int), |x| ("-", x));
+        write!(f, "{}{}", sign, group_thousands(int))?;
+        if !frac.is_empty() {
+            write!(f, ".{}", frac)?;
+        }
+        write!(f, " {}", self.currency)
+    }
+}
+
+/// An exchange api returning the price of a trading pair.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait PriceProvider: MaybeSync {
+    /// The name of the provider, as used by `Metadata::provider` and
+    /// `Metadata::pairs`.
+    fn name(&self) -> &str;
+
+    /// The last price of `pair`, in the exchange own symbol format.
+    async fn price(&self, pair: &str) -> Result<f64, RegistryError>;
+}
+
+fn parse_price(value: &Value) -> Result<f64, RegistryError> {
+    let price = match value {
+        Value::String(price) => price.parse().ok(),
+        Value::Number(price) => price.as_f64(),
+        _ => None,
+    };
+    price.ok_or_else(|| RegistryError::Price(format!("invalid price {}", value)))
+}
+
+/// Binance average price, eg. pair `BTCUSDT`.
+pub struct Binance<C = ReqwestClient> {
+    client: C,
+    base_url: String,
+}
+
+impl<C: HttpClient> Binance<C> {
+    pub fn new(client: C, base_url: &str) -> Self {
+        Binance {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Default for Binance {
+    fn default() -> Self {
+        Binance::new(ReqwestClient::default(), "https://api.binance.com")
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: HttpClient> PriceProvider for Binance<C> {
+    fn name(&self) -> &str {
+        "binance"
+    }
+
+    async fn price(&self, pair: &str) -> Result<f64, RegistryError> {
+        #[derive(Deserialize)]
+        struct AvgPrice {
+            price: Value,
+        }
+        let url = format!("{}/api/v3/avgPrice?symbol={}", self.base_url, pair);
+        let res: AvgPrice = serde_json::from_slice(&self.client.get(&url).await?)?;
+        parse_price(&res.price)
+    }
+}
+
+/// Kraken ticker last trade price, eg. pair `XBTUSDT`.
+pub struct Kraken<C = ReqwestClient> {
+    client: C,
+    base_url: String,
+}
+
+impl<C: HttpClient> Kraken<C> {
+    pub fn new(client: C, base_url: &str) -> Self {
+        Kraken {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Default for Kraken {
+    fn default() -> Self {
+        Kraken::new(ReqwestClient::default(), "https://api.kraken.com")
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: HttpClient> PriceProvider for Kraken<C> {
+    fn name(&self) -> &str {
+        "kraken"
+    }
+
+    async fn price(&self, pair: &str) -> Result<f64, RegistryError> {
+        #[derive(Deserialize)]
+        struct Ticker {
+            error: Vec<String>,
+            #[serde(default)]
+            result: serde_json::Map<String, Value>,
+        }
+        let url = format!("{}/0/public/Ticker?pair={}", self.base_url, pair);
+        let res: Ticker = serde_json::from_slice(&self.client.get(&url).await?)?;
+        if let Some(err) = res.error.first() {
+            return Err(RegistryError::Price(err.clone()));
+        }
+        // The result is keyed by the kraken pair name, eg. `XXBTZUSD`
+        let last = res.result.values().next().and_then(|x| x.pointer("/c/0"));
+        parse_price(last.unwrap_or(&Value::Null))
+    }
+}
+
+/// Bitfinex ticker last price, eg. pair `tBTCUSD`.
+pub struct Bitfinex<C = ReqwestClient> {
+    client: C,
+    base_url: String,
+}
+
+impl<C: HttpClient> Bitfinex<C> {
+    pub fn new(client: C, base_url: &str) -> Self {
+        Bitfinex {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Default for Bitfinex {
+    fn default() -> Self {
+        Bitfinex::new(ReqwestClient::default(), "https://api-pub.bitfinex.com")
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: HttpClient> PriceProvider for Bitfinex<C> {
+    fn name(&self) -> &str {
+        "bitfinex"
+    }
+
+    async fn price(&self, pair: &str) -> Result<f64, RegistryError> {
+        let url = format!("{}/v2/ticker/{}", self.base_url, pair);
+        let res: Vec<Value> = serde_json::from_slice(&self.client.get(&url).await?)?;
+        // [BID, BID_SIZE, ASK, ASK_SIZE, DAILY_CHANGE, DAILY_CHANGE_RELATIVE, LAST_PRICE, ...]
+        parse_price(res.get(6).unwrap_or(&Value::Null))
+    }
+}
+
+/// A generic json api returning the price of a pair, such as the SideSwap
+/// style market endpoints. `{pair}` in the url template is replaced with the
+/// pair, the price is read at the json `pointer` of the response.
+pub struct JsonApi<C = ReqwestClient> {
+    name: String,
+    client: C,
+    url: String,
+    pointer: String,
+}
+
+impl<C: HttpClient> JsonApi<C> {
+    /// Eg. `JsonApi::new("sideswap", client, "https://example.com/price/{pair}", "/price")`.
+    pub fn new(name: &str, client: C, url: &str, pointer: &str) -> Self {
+        JsonApi {
+            name: name.to_string(),
+            client,
+            url: url.to_string(),
+            pointer: pointer.to_string(),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: HttpClient> PriceProvider for JsonApi<C> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn price(&self, pair: &str) -> Result<f64, RegistryError> {
+        let url = self.url.replace("{pair}", pair);
+        let res: Value = serde_json::from_slice(&self.client.get(&url).await?)?;
+        parse_price(res.pointer(&self.pointer).unwrap_or(&Value::Null))
+    }
+}
+
+/// How the `PriceOracle` combines its providers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Ask the providers in priority order, returning the first price.
+    #[default]
+    Fallback,
+    /// Ask every provider at once, returning the median of the prices.
+    Median,
+}
+
+/// Prices assets through a chain of `PriceProvider`s.
+pub struct PriceOracle {
+    providers: Vec<Box<dyn PriceProvider>>,
+    strategy: Strategy,
+    btc_rates: IndexMap<Currency, Metadata>,
+}
+
+impl PriceOracle {
+    /// `providers` are in priority order.
+    pub fn new(providers: Vec<Box<dyn PriceProvider>>, strategy: Strategy) -> Self {
+        PriceOracle {
+            providers,
+            strategy,
+            btc_rates: Self::default_btc_rates(),
+        }
+    }
+
+    /// The pairs pricing BTC in fiat currencies on the default providers.
+    fn default_btc_rates() -> IndexMap<Currency, Metadata> {
+        let metadata = |pair: &str, kraken: &str, bitfinex: &str| Metadata {
+            pair: Some(pair.to_string()),
+            pairs: Some(IndexMap::from([
+                ("kraken".to_string(), kraken.to_string()),
+                ("bitfinex".to_string(), bitfinex.to_string()),
+            ])),
+            ..Default::default()
+        };
+        IndexMap::from([
+            (Currency::Usd, metadata("BTCUSDT", "XBTUSD", "tBTCUSD")),
+            (Currency::Eur, metadata("BTCEUR", "XBTEUR", "tBTCEUR")),
+        ])
+    }
+
+    /// Set the pair pricing BTC in the fiat `currency`, used to convert
+    /// prices between currencies.
+    pub fn with_btc_rate(mut self, currency: Currency, metadata: Metadata) -> Self {
+        self.btc_rates.insert(currency, metadata);
+        self
+    }
+
+    /// The price of one BTC in `currency`.
+    async fn btc_price(&self, currency: Currency) -> Result<f64, RegistryError> {
+        match currency {
+            Currency::Btc => Ok(1.0),
+            Currency::Sat => Ok(100_000_000.0),
+            _ => match self.btc_rates.get(&currency) {
+                Some(metadata) => self.price(metadata).await,
+                None => Err(RegistryError::Price(format!("no rate for {}", currency))),
+            },
+        }
+    }
+
+    /// The value of one unit of `from` in `to`, derived through BTC.
+    pub async fn rate(&self, from: Currency, to: Currency) -> Result<f64, RegistryError> {
+        if from == to {
+            return Ok(1.0);
+        }
+        Ok(self.btc_price(to).await? / self.btc_price(from).await?)
+    }
+
+    /// The price of one unit of the asset with the given metadata in the
+    /// `reference` currency, following `Metadata::price`.
+    pub async fn quote(
+        &self,
+        metadata: &Metadata,
+        reference: Currency,
+    ) -> Result<Quote, RegistryError> {
+        let (value, currency) = match metadata.price.as_ref() {
+            Some(PriceDefinition::Peg { currency }) => (1.0, *currency),
+            Some(PriceDefinition::Pair { currency, invert }) => {
+                let price = self.price(metadata).await?;
+                if *invert {
+                    (1.0 / price, *currency)
+                } else {
+                    (price, *currency)
+                }
+            }
+            None => return Err(RegistryError::Price("no price definition".into())),
+        };
+        Ok(Quote {
+            value: value * self.rate(currency, reference).await?,
+            currency: reference,
+        })
+    }
+
+    /// The pair of each provider able to price the asset: the provider chosen
+    /// in `Metadata::provider` only, or every provider otherwise.
+    fn pairs<'a>(&'a self, metadata: &'a Metadata) -> Vec<(&'a dyn PriceProvider, &'a str)> {
+        self.providers
+            .iter()
+            .map(|x| x.as_ref())
+            .filter(|x| {
+                metadata
+                    .provider
+                    .as_ref()
+                    .is_none_or(|provider| provider == x.name())
+            })
+            .filter_map(|x| metadata.pair_for(x.name()).map(|pair| (x, pair)))
+            .collect()
+    }
+
+    /// The raw price of the trading pair of the metadata, in the pair own
+    /// terms. See `PriceOracle::quote` for the price of the asset.
+    pub async fn price(&self, metadata: &Metadata) -> Result<f64, RegistryError> {
+        let pairs = self.pairs(metadata);
+        let mut last_err = None;
+        match self.strategy {
+            Strategy::Fallback => {
+                for (provider, pair) in pairs {
+                    match provider.price(pair).await {
+                        Ok(price) => return Ok(price),
+                        Err(err) => last_err = Some(err),
+                    }
+                }
+            }
+            Strategy::Median => {
+                let results = join_all(pairs.iter().map(|(x, pair)| x.price(pair))).await;
+                let mut prices = vec![];
+                for result in results {
+                    match result {
+                        Ok(price) => prices.push(price),
+                        Err(err) => last_err = Some(err),
+                    }
+                }
+                if let Some(median) = median(prices) {
+                    return Ok(median);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| RegistryError::Price("no provider for the asset".into())))
+    }
+}
+
+impl Default for PriceOracle {
+    /// Binance, Kraken and Bitfinex, in this order, with `Strategy::Fallback`.
+    fn default() -> Self {
+        PriceOracle::new(
+            vec![
+                Box::new(Binance::default()),
+                Box::new(Kraken::default()),
+                Box::new(Bitfinex::default()),
+            ],
+            Strategy::Fallback,
+        )
+    }
+}
+
+fn median(mut prices: Vec<f64>) -> Option<f64> {
+    prices.sort_by(|a, b| a.total_cmp(b));
+    let mid = prices.len() / 2;
+    match prices.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((prices[mid - 1] + prices[mid]) / 2.0),
+        _ => Some(prices[mid]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::http::StubClient;
+
+    const BINANCE: &str = "http://binance/api/v3/avgPrice?symbol=";
+    const KRAKEN: &str = "http://kraken/0/public/Ticker?pair=";
+    const BITFINEX: &str = "http://bitfinex/v2/ticker/";
+
+    fn binance(pair: &str, price: &str) -> StubClient {
+        StubClient::default().with(
+            &format!("{}{}", BINANCE, pair),
+            &format!(r#"{{"mins":5,"price":"{}"}}"#, price),
+        )
+    }
+
+    fn kraken(pair: &str, price: &str) -> StubClient {
+        StubClient::default().with(
+            &format!("{}{}", KRAKEN, pair),
+            &format!(
+                r#"{{"error":[],"result":{{"XXBTZUSD":{{"a":["1","1","1.0"],"c":["{}","0.01"]}}}}}}"#,
+                price
+            ),
+        )
+    }
+
+    fn bitfinex(pair: &str, price: f64) -> StubClient {
+        StubClient::default().with(
+            &format!("{}{}", BITFINEX, pair),
+            &format!("[1.0,2.0,3.0,4.0,5.0,0.01,{},8.0,9.0,10.0]", price),
+        )
+    }
+
+    fn oracle(
+        strategy: Strategy,
+        binance: StubClient,
+        kraken: StubClient,
+        bitfinex: StubClient,
+    ) -> PriceOracle {
+        PriceOracle::new(
+            vec![
+                Box::new(Binance::new(binance, "http://binance/")),
+                Box::new(Kraken::new(kraken, "http://kraken")),
+                Box::new(Bitfinex::new(bitfinex, "http://bitfinex")),
+            ],
+            strategy,
+        )
+    }
+
+    /// Binance is down, kraken and bitfinex reply.
+    fn without_binance(strategy: Strategy) -> PriceOracle {
+        let binance = StubClient::default().with_status(&format!("{}BTCUSDT", BINANCE), 503);
+        oracle(
+            strategy,
+            binance,
+            kraken("XBTUSD", "110"),
+            bitfinex("tBTCUSD", 130.0),
+        )
+    }
+
+    /// Every provider fails.
+    fn offline(strategy: Strategy) -> PriceOracle {
+        oracle(
+            strategy,
+            StubClient::default(),
+            StubClient::default(),
+            StubClient::default(),
+        )
+    }
+
+    /// The metadata pricing BTC in USD, as in `PriceOracle::default_btc_rates`.
+    fn btc_usd() -> Metadata {
+        PriceOracle::default_btc_rates()[&Currency::Usd].clone()
+    }
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!(
+            (value - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+            "{} != {}",
+            value,
+            expected
+        );
+    }
+
+    #[test]
+    fn providers() {
+        let client = binance("BTCUSDT", "65000.12");
+        let price = block_on(Binance::new(client, "http://binance").price("BTCUSDT"));
+        assert_close(price.unwrap(), 65000.12);
+
+        let client = kraken("XBTUSD", "64000.5");
+        let price = block_on(Kraken::new(client, "http://kraken").price("XBTUSD"));
+        assert_close(price.unwrap(), 64000.5);
+
+        let client = bitfinex("tBTCUSD", 63000.25);
+        let price = block_on(Bitfinex::new(client, "http://bitfinex").price("tBTCUSD"));
+        assert_close(price.unwrap(), 63000.25);
+
+        let client =
+            StubClient::default().with("http://sideswap/price/DePix", r#"{"data":{"price":0.5}}"#);
+        let api = JsonApi::new(
+            "sideswap",
+            client,
+            "http://sideswap/price/{pair}",
+            "/data/price",
+        );
+        assert_eq!(api.name(), "sideswap");
+        assert_close(block_on(api.price("DePix")).unwrap(), 0.5);
+    }
+
+    #[test]
+    fn provider_errors() {
+        let client = StubClient::default().with(
+            &format!("{}XBTEUR", KRAKEN),
+            r#"{"error":["EQuery:Unknown asset pair"]}"#,
+        );
+        let price = block_on(Kraken::new(client, "http://kraken").price("XBTEUR"));
+        assert!(matches!(price, Err(RegistryError::Price(_))));
+
+        let client = StubClient::default().with(&format!("{}tBTCEUR", BITFINEX), "[1.0,2.0]");
+        let price = block_on(Bitfinex::new(client, "http://bitfinex").price("tBTCEUR"));
+        assert!(matches!(price, Err(RegistryError::Price(_))));
+
+        let client = StubClient::default().with(&format!("{}BTCEUR", BINANCE), "not json");
+        let price = block_on(Binance::new(client, "http://binance").price("BTCEUR"));
+        assert!(matches!(price, Err(RegistryError::Parse(_))));
+    }
+
+    #[test]
+    fn fallback() {
+        let metadata = btc_usd();
+        let oracle = oracle(
+            Strategy::Fallback,
+            binance("BTCUSDT", "100"),
+            kraken("XBTUSD", "110"),
+            bitfinex("tBTCUSD", 130.0),
+        );
+        assert_close(block_on(oracle.price(&metadata)).unwrap(), 100.0);
+
+        // Binance fails, kraken is asked before bitfinex
+        let oracle = without_binance(Strategy::Fallback);
+        assert_close(block_on(oracle.price(&metadata)).unwrap(), 110.0);
+
+        let oracle = offline(Strategy::Fallback);
+        let price = block_on(oracle.price(&metadata));
+        assert!(matches!(price, Err(RegistryError::HttpStatus(404))));
+    }
+
+    #[test]
+    fn provider_restriction() {
+        let oracle = oracle(
+            Strategy::Fallback,
+            binance("BTCUSDT", "100"),
+            kraken("XBTUSD", "110"),
+            bitfinex("tBTCUSD", 130.0),
+        );
+        let metadata = Metadata {
+            provider: Some("bitfinex".into()),
+            ..btc_usd()
+        };
+        assert_close(block_on(oracle.price(&metadata)).unwrap(), 130.0);
+
+        let metadata = Metadata {
+            provider: Some("coinbase".into()),
+            ..btc_usd()
+        };
+        let price = block_on(oracle.price(&metadata));
+        assert!(matches!(price, Err(RegistryError::Price(_))));
+    }
+
+    #[test]
+    fn median_strategy() {
+        let metadata = btc_usd();
+        let oracle = oracle(
+            Strategy::Median,
+            binance("BTCUSDT", "100"),
+            kraken("XBTUSD", "110"),
+            bitfinex("tBTCUSD", 130.0),
+        );
+        assert_close(block_on(oracle.price(&metadata)).unwrap(), 110.0);
+
+        let oracle = without_binance(Strategy::Median);
+        assert_close(block_on(oracle.price(&metadata)).unwrap(), 120.0);
+
+        let oracle = offline(Strategy::Median);
+        assert!(block_on(oracle.price(&metadata)).is_err());
+
+        assert_eq!(median(vec![3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(vec![4.0, 1.0, 3.0, 2.0]), Some(2.5));
+        assert_eq!(median(vec![]), None);
+    }
+
+    #[test]
+    fn quote() {
+        let oracle = oracle(
+            Strategy::Fallback,
+            binance("BTCUSDT", "100")
+                .with(&format!("{}BTCEUR", BINANCE), r#"{"price":"90"}"#)
+                .with(&format!("{}PEPEUSDT", BINANCE), r#"{"price":"0.00001"}"#),
+            StubClient::default(),
+            StubClient::default(),
+        );
+        let pepe = Metadata {
+            pair: Some("PEPEUSDT".into()),
+            price: Some(PriceDefinition::Pair {
+                currency: Currency::Usd,
+                invert: false,
+            }),
+            ..Default::default()
+        };
+        let quote = block_on(oracle.quote(&pepe, Currency::Usd)).unwrap();
+        assert_eq!(quote.currency, Currency::Usd);
+        assert_close(quote.value, 0.00001);
+        // Converted through BTC: 0.00001 USD * 90 EUR/BTC / 100 USD/BTC
+        let quote = block_on(oracle.quote(&pepe, Currency::Eur)).unwrap();
+        assert_eq!(quote.currency, Currency::Eur);
+        assert_close(quote.value, 0.000009);
+
+        // BTCUSDT prices USDt in BTC as 1 / price
+        let usdt = Metadata {
+            pair: Some("BTCUSDT".into()),
+            price: Some(PriceDefinition::Pair {
+                currency: Currency::Btc,
+                invert: true,
+            }),
+            ..Default::default()
+        };
+        assert_close(
+            block_on(oracle.quote(&usdt, Currency::Btc)).unwrap().value,
+            0.01,
+        );
+        assert_close(
+            block_on(oracle.quote(&usdt, Currency::Sat)).unwrap().value,
+            1_000_000.0,
+        );
+        assert_close(
+            block_on(oracle.quote(&usdt, Currency::Usd)).unwrap().value,
+            1.0,
+        );
+
+        let lbtc = Metadata {
+            price: Some(PriceDefinition::Peg {
+                currency: Currency::Btc,
+            }),
+            ..Default::default()
+        };
+        assert_close(
+            block_on(oracle.quote(&lbtc, Currency::Eur)).unwrap().value,
+            90.0,
+        );
+
+        let quote = block_on(oracle.quote(&Metadata::default(), Currency::Usd));
+        assert!(matches!(quote, Err(RegistryError::Price(_))));
+    }
+}
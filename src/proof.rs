@@ -0,0 +1,141 @@
+//! Verification of the domain ownership proof of registered assets.
+//!
+//! To register an asset the issuer publishes a proof on the domain declared in
+//! `entity.domain`, at `https://<domain>/.well-known/liquid-asset-proof-<asset_id>`,
+//! containing the text
+//! `Authorize linking the domain name <domain> to the Liquid asset <asset_id>`.
+
+use elements::AssetId;
+
+use crate::asset_entry::AssetEntry;
+use crate::error::RegistryError;
+use crate::http::HttpClient;
+
+/// The text the proof of `asset_id` published on `domain` must contain.
+pub fn proof_text(domain: &str, asset_id: &AssetId) -> String {
+    format!(
+        "Authorize linking the domain name {} to the Liquid asset {}",
+        domain, asset_id
+    )
+}
+
+/// Whether `content` is a valid proof linking `domain` to `asset_id`.
+pub fn validate(content: &str, domain: &str, asset_id: &AssetId) -> bool {
+    content.trim() == proof_text(domain, asset_id)
+}
+
+/// Fetches and validates the domain ownership proofs through an `HttpClient`.
+pub struct ProofVerifier<C> {
+    client: C,
+    base_url: Option<String>,
+}
+
+impl<C: HttpClient> ProofVerifier<C> {
+    pub fn new(client: C) -> Self {
+        ProofVerifier {
+            client,
+            base_url: None,
+        }
+    }
+
+    /// Fetch the proofs from `base_url` instead of `https://<domain>`, eg.
+    /// a local server standing in for the issuers domains.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// The url of the proof of `asset_id` published on `domain`.
+    pub fn proof_url(&self, domain: &str, asset_id: &AssetId) -> String {
+        let base_url = match &self.base_url {
+            Some(base_url) => base_url.clone(),
+            None => format!("https://{}", domain),
+        };
+        format!("{}/.well-known/liquid-asset-proof-{}", base_url, asset_id)
+    }
+
+    /// Fetch the proof of the asset from its domain and validate it. Returns
+    /// an error when the entry has no domain or the proof can't be fetched.
+    pub async fn verify(&self, entry: &AssetEntry) -> Result<bool, RegistryError> {
+        let domain = entry
+            .domain()
+            .ok_or_else(|| RegistryError::Verification("missing domain".into()))?;
+        let url = self.proof_url(domain, &entry.asset_id);
+        let content = self.client.get_text(&url).await?;
+        Ok(validate(&content, domain, &entry.asset_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    use super::*;
+    use crate::http::StubClient;
+
+    const USDT: &str = "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2";
+
+    fn entry(domain: Option<&str>) -> AssetEntry {
+        AssetEntry {
+            asset_id: AssetId::from_str(USDT).unwrap(),
+            entity: domain.map(|x| json!({ "domain": x })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validation() {
+        let asset_id = AssetId::from_str(USDT).unwrap();
+        let text = proof_text("tether.to", &asset_id);
+        assert!(validate(&text, "tether.to", &asset_id));
+        assert!(validate(&format!("\n{}\n", text), "tether.to", &asset_id));
+        assert!(!validate(&text, "example.com", &asset_id));
+        assert!(!validate("", "tether.to", &asset_id));
+    }
+
+    #[test]
+    fn url() {
+        let asset_id = AssetId::from_str(USDT).unwrap();
+        let verifier = ProofVerifier::new(StubClient::default());
+        assert_eq!(
+            verifier.proof_url("tether.to", &asset_id),
+            format!("https://tether.to/.well-known/liquid-asset-proof-{}", USDT)
+        );
+        let verifier = verifier.with_base_url("http://localhost:8080/");
+        assert_eq!(
+            verifier.proof_url("tether.to", &asset_id),
+            format!(
+                "http://localhost:8080/.well-known/liquid-asset-proof-{}",
+                USDT
+            )
+        );
+    }
+
+    #[test]
+    fn verify() {
+        let asset_id = AssetId::from_str(USDT).unwrap();
+        let url = format!("http://proofs/.well-known/liquid-asset-proof-{}", USDT);
+        let verify = |client: StubClient, entry: &AssetEntry| {
+            block_on(
+                ProofVerifier::new(client)
+                    .with_base_url("http://proofs")
+                    .verify(entry),
+            )
+        };
+
+        let valid = StubClient::default().with(&url, &proof_text("tether.to", &asset_id));
+        assert!(verify(valid, &entry(Some("tether.to"))).unwrap());
+
+        let invalid = StubClient::default().with(&url, &proof_text("example.com", &asset_id));
+        assert!(!verify(invalid, &entry(Some("tether.to"))).unwrap());
+
+        let missing = verify(StubClient::default(), &entry(Some("tether.to")));
+        assert!(matches!(missing, Err(RegistryError::HttpStatus(404))));
+
+        let no_domain = verify(StubClient::default(), &entry(None));
+        assert!(matches!(no_domain, Err(RegistryError::Verification(_))));
+    }
+}
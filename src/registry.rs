@@ -1,47 +1,107 @@
-use std::collections::HashMap;
+use std::sync::OnceLock;
 
 use elements::AssetId;
-use gloo::console::console_dbg;
+use futures::stream::{self, StreamExt};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Error};
+use serde_json::json;
 
+use crate::amount::Amount;
 use crate::asset::{Asset, Metadata};
 use crate::asset_entry::AssetEntry;
+use crate::audit::Audit;
+use crate::backend::ChainBackend;
+use crate::error::RegistryError;
+use crate::export::ExportFormat;
+pub use crate::filter::Filter;
+use crate::import::{Conflict, Import};
+use crate::market::Market;
+use crate::price::{Currency, PriceOracle, Quote};
+use crate::search::{Match, SearchIndex};
+use crate::source::{Format, Sources};
 
-#[derive(Deserialize, Debug)]
-struct BinancePrice {
-    price: String,
-}
+const INDEX_MINIMAL: &str = std::include_str!("../assets/liquid_assets_minimal.json");
+#[cfg(feature = "full")]
+const INDEX_FULL: Option<&str> = Some(std::include_str!("../assets/liquid_assets.json"));
+#[cfg(not(feature = "full"))]
+const INDEX_FULL: Option<&str> = None;
+const METADATA: &str = std::include_str!("../assets/liquid_metadatas.json");
+
+/// An entry of `index.minimal.json`: domain, ticker, name and precision.
+type MinimalEntry = (Option<String>, Option<String>, Option<String>, u8);
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Registry {
+    /// The assets of the registry. Call `Registry::reindex` after changing
+    /// them, so that searches see the changes.
     pub assets: IndexMap<AssetId, Asset>,
-}
-pub enum Filter {
-    All,
-    Main,
-    Amp,
-    Stablecoins,
-    Iconed,
-    Text(String),
+    /// Built on the first text search.
+    #[serde(skip)]
+    search: OnceLock<SearchIndex>,
 }
 impl Registry {
+    /// Build the registry from the copies embedded in the binary: the full
+    /// index with the `full` feature, the minimal one otherwise.
     pub fn new() -> Self {
-        let mut registry = Registry {
-            assets: IndexMap::default(),
-        };
-        registry.assets = registry.download().unwrap();
+        let mut registry = Registry::from_assets(IndexMap::default());
+        registry.assets = registry
+            .download()
+            .expect("embedded registry files are valid");
         registry
     }
 
-    pub fn download(&mut self) -> Result<IndexMap<AssetId, Asset>, Error> {
-        let metadatas = Self::get_metadata().unwrap();
-        let icons = Self::get_icons().unwrap();
-        let mut assets = IndexMap::default();
-        let asset_entries = Self::get_assets_minimal().unwrap();
-        //let asset_entries = Self::get_assets().unwrap();
+    /// Build the registry from the given assets.
+    pub fn from_assets(assets: IndexMap<AssetId, Asset>) -> Self {
+        Registry {
+            assets,
+            search: OnceLock::new(),
+        }
+    }
+
+    /// Load the registry asynchronously from the given sources. A source that
+    /// can't be downloaded falls back to the embedded copy. Icons are optional:
+    /// the registry is loaded without them when they can't be downloaded.
+    /// The full index has an embedded copy only with the `full` feature: it
+    /// fails to load otherwise, rather than loading the minimal index.
+    pub async fn load(sources: &Sources) -> Result<Self, RegistryError> {
+        let asset_entries = match sources.format {
+            Format::Minimal => {
+                let index = sources.index.read(Some(INDEX_MINIMAL)).await?;
+                Self::get_assets_minimal(index.as_deref().unwrap_or(b"{}"))?
+            }
+            Format::Full => match sources.index.read(INDEX_FULL).await? {
+                Some(index) => Self::get_assets(&index)?,
+                None => {
+                    return Err(RegistryError::MissingSource(
+                        "the embedded full index".into(),
+                    ))
+                }
+            },
+        };
+        let icons = sources.icons.read(None).await.ok().flatten();
+        let metadata = sources.metadata.read(Some(METADATA)).await?;
+        Ok(Registry::from_assets(Self::build(
+            asset_entries,
+            Self::get_icons(icons.as_deref().unwrap_or(b"{}"))?,
+            Self::get_metadata(metadata.as_deref().unwrap_or(b"{}"))?,
+        )))
+    }
 
+    pub fn download(&mut self) -> Result<IndexMap<AssetId, Asset>, RegistryError> {
+        let metadatas = Self::get_metadata(METADATA.as_bytes())?;
+        let asset_entries = match INDEX_FULL {
+            Some(index) => Self::get_assets(index.as_bytes())?,
+            None => Self::get_assets_minimal(INDEX_MINIMAL.as_bytes())?,
+        };
+        Ok(Self::build(asset_entries, IndexMap::default(), metadatas))
+    }
+
+    fn build(
+        asset_entries: IndexMap<AssetId, AssetEntry>,
+        icons: IndexMap<AssetId, String>,
+        metadatas: IndexMap<AssetId, Metadata>,
+    ) -> IndexMap<AssetId, Asset> {
+        let mut assets = IndexMap::default();
         for asset in asset_entries {
             assets.insert(
                 asset.0,
@@ -54,19 +114,20 @@ impl Registry {
                 },
             );
         }
-        Ok(assets)
+        assets
     }
 
-    fn get_assets_minimal() -> Result<IndexMap<AssetId, AssetEntry>, Error> {
-        let content = std::include_str!("../assets/liquid_assets_minimal.json");
-        let values: IndexMap<AssetId, serde_json::Value> = serde_json::from_str(content).unwrap();
+    /// Parse the `index.minimal.json` format, where each asset is an array of
+    /// `[domain, ticker, name, precision]`.
+    fn get_assets_minimal(content: &[u8]) -> Result<IndexMap<AssetId, AssetEntry>, RegistryError> {
+        let values: IndexMap<AssetId, MinimalEntry> = serde_json::from_slice(content)?;
         let mut assets = IndexMap::default();
 
         for asset in values {
-            let value = asset.1.as_array().unwrap();
+            let (domain, ticker, name, precision) = asset.1;
             let mut entity = json!({});
-            if let Some(value) = value[0].as_str() {
-                entity = json!({ "domain": value });
+            if let Some(domain) = domain {
+                entity = json!({ "domain": domain });
             }
             assets.insert(
                 asset.0,
@@ -77,9 +138,9 @@ impl Registry {
                     issuance_prevout: None,
                     issuance_txin: None,
                     issuer_pubkey: None,
-                    name: value[2].as_str().unwrap_or("").into(),
-                    precision: value[3].as_number().unwrap().as_u64().unwrap() as u8,
-                    ticker: value[1].as_str().and_then(|x| Some(x.to_string())),
+                    name: name.unwrap_or_default(),
+                    precision,
+                    ticker,
                     version: None,
                 },
             );
@@ -87,110 +148,322 @@ impl Registry {
         Ok(assets)
     }
 
-    fn get_assets() -> Result<IndexMap<AssetId, AssetEntry>, Error> {
-        let content = std::include_str!("../assets/liquid_assets.json");
-        serde_json::from_str(content)
+    /// Parse the `index.json` format, where each asset is a full `AssetEntry`.
+    fn get_assets(content: &[u8]) -> Result<IndexMap<AssetId, AssetEntry>, RegistryError> {
+        Ok(serde_json::from_slice(content)?)
     }
 
-    fn get_icons() -> Result<IndexMap<AssetId, String>, Error> {
-        let content = std::include_str!("../assets/liquid_icons.json");
-        serde_json::from_str(content)
+    fn get_icons(content: &[u8]) -> Result<IndexMap<AssetId, String>, RegistryError> {
+        Ok(serde_json::from_slice(content)?)
     }
 
-    fn get_metadata() -> Result<IndexMap<AssetId, Metadata>, Error> {
-        let content = std::include_str!("../assets/liquid_metadatas.json");
-        serde_json::from_str(content)
+    fn get_metadata(content: &[u8]) -> Result<IndexMap<AssetId, Metadata>, RegistryError> {
+        Ok(serde_json::from_slice(content)?)
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Registry {
-    pub async fn query_by_id(&self, id: AssetId) -> Result<&Asset, Error> {
-        Ok(self.assets.get(&id).unwrap())
+    pub async fn query_by_id(&self, id: AssetId) -> Result<&Asset, RegistryError> {
+        self.assets.get(&id).ok_or(RegistryError::NotFound(id))
     }
-    pub async fn query_by_ids(&self, ids: Vec<AssetId>) -> Result<Vec<&Asset>, Error> {
+    pub async fn query_by_ids(&self, ids: Vec<AssetId>) -> Result<Vec<&Asset>, RegistryError> {
         Ok(ids.iter().filter_map(|i| self.assets.get(i)).collect())
     }
-    pub async fn query(&self, filter: Filter) -> Result<Vec<AssetId>, Error> {
-        match filter {
-            Filter::All => Ok(self.assets.values().map(|x| x.asset_id).collect()),
-            Filter::Main => Ok(self
-                .assets
-                .values()
-                .filter(|x| {
-                    x.metadata
-                        .as_ref()
-                        .is_some_and(|x| x.weight.is_some_and(|x| x > 0))
-                })
-                .map(|x| x.asset_id)
-                .collect()),
-            Filter::Amp => Ok(self
-                .assets
-                .values()
-                .filter(|x| {
-                    x.metadata
-                        .as_ref()
-                        .is_some_and(|x| x.amp.is_some_and(|x| x))
-                })
+    /// The assets matching the filter: ranked by the text match when the
+    /// filter has free text, see `Filter::text`, in registry order otherwise.
+    pub async fn query(&self, filter: Filter) -> Result<Vec<AssetId>, RegistryError> {
+        match filter.text() {
+            // Assets removed without `reindex` may still be in the index
+            Some(text) => Ok(self
+                .search(text)
+                .into_iter()
+                .filter_map(|x| self.assets.get(&x.asset_id))
+                .filter(|x| filter.matches(x))
                 .map(|x| x.asset_id)
                 .collect()),
-            Filter::Iconed => Ok(self
+            None => Ok(self
                 .assets
                 .values()
-                .filter(|x| x.icon.as_ref().is_some())
-                .map(|x| x.asset_id)
-                .collect()),
-            Filter::Stablecoins => Ok(self
-                .assets
-                .values()
-                .filter(|x| {
-                    x.metadata
-                        .as_ref()
-                        .is_some_and(|x| x.stablecoin.as_ref().is_some_and(|x| *x == true))
-                })
-                .map(|x| x.asset_id)
-                .collect()),
-            Filter::Text(text) => Ok(self
-                .assets
-                .values()
-                .filter(|x| x.filter(&text))
+                .filter(|x| filter.matches(x))
                 .map(|x| x.asset_id)
                 .collect()),
         }
     }
 
-    pub async fn fetch(&self, asset_id: AssetId) -> Result<AssetEntry, Error> {
-        let url = format!("https://blockstream.info/liquid/api/asset/{}", asset_id);
-        let res = reqwest::get(url).await.unwrap();
-        let asset: AssetEntry = res.json().await.unwrap();
-        println!("Asset: {:#?}", asset);
-        Ok(asset)
+    /// Export the assets matching the filter, in the order of
+    /// `Registry::query`. `timestamp`, in seconds since the unix epoch, is
+    /// the time of token lists.
+    pub async fn export(
+        &self,
+        filter: Filter,
+        format: ExportFormat,
+        timestamp: u64,
+    ) -> Result<String, RegistryError> {
+        let ids = self.query(filter).await?;
+        let assets: Vec<&Asset> = ids.iter().filter_map(|x| self.assets.get(x)).collect();
+        format.export(&assets, timestamp)
+    }
+
+    /// The assets matching the text, best first, see `search::Tier` for the
+    /// ranking. Case and diacritics are ignored and a few typos tolerated.
+    pub fn search(&self, text: &str) -> Vec<Match> {
+        self.search
+            .get_or_init(|| SearchIndex::new(self.assets.values()))
+            .search(text)
+    }
+
+    /// Verify every asset of the registry against its contract. Only the
+    /// entries of the full index can be verified.
+    pub fn audit(&self) -> Audit {
+        Audit::run(self.assets.values())
+    }
+
+    /// Drop the search index, rebuilt with the current assets on the next
+    /// search.
+    pub fn reindex(&mut self) {
+        self.search = OnceLock::new();
+    }
+
+    /// Merge the metadata of the imports, in order, into the metadata of the
+    /// assets, see `Precedence` for how the values merge. Returns the fields
+    /// set to different values. Assets not in the registry are skipped.
+    pub fn import(&mut self, imports: &[Import]) -> Vec<Conflict> {
+        let mut conflicts = vec![];
+        for import in imports {
+            for asset_id in import.metadata.keys() {
+                if let Some(asset) = self.assets.get_mut(asset_id) {
+                    let metadata = asset.metadata.get_or_insert_with(Default::default);
+                    import.merge(*asset_id, metadata, &mut conflicts);
+                }
+            }
+        }
+        // The weight may have changed
+        self.reindex();
+        conflicts
+    }
+
+    /// Upgrade the entry of the asset to the full entry fetched with
+    /// `Registry::fetch`, returning the merged entry. Entries already full are
+    /// returned without fetching. Use `Registry::merge` to store the result.
+    pub async fn upgrade<B: ChainBackend + ?Sized>(
+        &self,
+        backend: &B,
+        asset_id: AssetId,
+    ) -> Result<AssetEntry, RegistryError> {
+        let asset = self.query_by_id(asset_id).await?;
+        match asset.asset_entry.clone() {
+            Some(entry) if entry.is_full() => Ok(entry),
+            Some(mut entry) => {
+                entry.merge(self.fetch(backend, asset_id).await?);
+                Ok(entry)
+            }
+            None => self.fetch(backend, asset_id).await,
+        }
+    }
+
+    /// Merge a full entry into the entry of the same asset in the registry.
+    pub fn merge(&mut self, entry: AssetEntry) {
+        if let Some(asset) = self.assets.get_mut(&entry.asset_id) {
+            match asset.asset_entry.as_mut() {
+                Some(current) => current.merge(entry),
+                None => asset.asset_entry = Some(entry),
+            }
+            // The domain may have changed
+            self.reindex();
+        }
+    }
+
+    /// Fetch the full entry of the asset from the backend.
+    pub async fn fetch<B: ChainBackend + ?Sized>(
+        &self,
+        backend: &B,
+        asset_id: AssetId,
+    ) -> Result<AssetEntry, RegistryError> {
+        backend.asset(asset_id).await
+    }
+
+    /// Fetch the circulating supply of the asset from the backend, with the
+    /// precision of the asset.
+    pub async fn supply<B: ChainBackend + ?Sized>(
+        &self,
+        backend: &B,
+        asset_id: AssetId,
+    ) -> Result<Amount, RegistryError> {
+        let precision = self.query_by_id(asset_id).await?.precision();
+        Ok(Amount::from_sat(backend.supply(asset_id).await?, precision))
+    }
+
+    /// The market capitalization of the asset in the `reference` currency:
+    /// the circulating supply at the price of the oracle.
+    pub async fn market_cap<B: ChainBackend + ?Sized>(
+        &self,
+        backend: &B,
+        oracle: &PriceOracle,
+        asset_id: AssetId,
+        reference: Currency,
+    ) -> Result<Quote, RegistryError> {
+        let price = self.price(oracle, asset_id, reference).await?;
+        let supply = self.supply(backend, asset_id).await?;
+        Ok(supply.value(&price))
+    }
+
+    /// Fetch supply and price of the assets, running at most `concurrency`
+    /// requests at a time to stay within the rate limits of the backend and
+    /// of the price providers. Assets without a price definition are priced
+    /// as `None` without querying the providers.
+    pub async fn markets<B: ChainBackend + ?Sized>(
+        &self,
+        backend: &B,
+        oracle: &PriceOracle,
+        asset_ids: &[AssetId],
+        reference: Currency,
+        concurrency: usize,
+    ) -> IndexMap<AssetId, Market> {
+        stream::iter(asset_ids.iter().copied())
+            .map(|asset_id| async move {
+                let supply = self.supply(backend, asset_id).await.ok();
+                let price = self.price(oracle, asset_id, reference).await.ok();
+                (asset_id, Market { supply, price })
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await
     }
 
-    pub async fn supply(&self, asset_id: AssetId) -> Result<String, Error> {
-        let url = format!(
-            "https://blockstream.info/liquid/api/asset/{}/supply/decimal",
-            asset_id
+    /// The price of one unit of the asset in the `reference` currency,
+    /// through the providers of the oracle.
+    pub async fn price(
+        &self,
+        oracle: &PriceOracle,
+        asset_id: AssetId,
+        reference: Currency,
+    ) -> Result<Quote, RegistryError> {
+        let metadata = self
+            .query_by_id(asset_id)
+            .await?
+            .metadata
+            .as_ref()
+            .filter(|x| x.is_priced())
+            .ok_or(RegistryError::NoPricePair(asset_id))?;
+        oracle.quote(metadata, reference).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Esplora;
+    use crate::http::StubClient;
+    use crate::source::Source;
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Registry>();
+    }
+
+    #[test]
+    fn query_without_reindex() {
+        let mut registry = Registry::new();
+        let text = Filter::Text("usdt".into());
+        let before = futures::executor::block_on(registry.query(text.clone())).unwrap();
+        registry.assets.shift_remove(&before[0]);
+        let after = futures::executor::block_on(registry.query(text)).unwrap();
+        assert_eq!(after.len(), before.len() - 1);
+        assert!(!after.contains(&before[0]));
+    }
+
+    #[test]
+    fn upgrade() {
+        let usdt = crate::asset_entry::tests::usdt();
+        let index = format!(
+            r#"{{
+                "{0}": {{
+                    "asset_id": "{0}",
+                    "entity": {{ "domain": "tether.to" }},
+                    "issuer_pubkey": "registry",
+                    "name": "Tether USD",
+                    "precision": 8,
+                    "ticker": "USDt"
+                }}
+            }}"#,
+            usdt.asset_id
         );
-        let res = reqwest::get(url).await.unwrap();
-        let supply = res.text().await.unwrap();
-        println!("Supply: {:#?}", supply);
-        Ok(supply)
-    }
-
-    pub async fn price(&self, asset_id: AssetId) -> Result<String, Error> {
-        let pair = self
-            .assets
-            .get(&asset_id)
-            .and_then(|a| a.metadata.as_ref().and_then(|x| x.pair.as_ref()));
-        match pair {
-            Some(pair) => {
-                let url = format!("https://api.binance.com/api/v3/avgPrice?symbol={}", pair);
-                let res = reqwest::get(url).await.unwrap();
-                let price = res.json::<BinancePrice>().await.unwrap();
-                println!("Price: {:#?}", price.price);
-                return Ok(price.price);
+        let sources = Sources {
+            format: Format::Full,
+            index: Source::Bytes(index.into_bytes()),
+            ..Default::default()
+        };
+        let mut registry = futures::executor::block_on(Registry::load(&sources)).unwrap();
+        let url = format!("http://esplora/api/asset/{}", usdt.asset_id);
+        let client = StubClient::default().with(&url, &serde_json::to_string(&usdt).unwrap());
+        let esplora = Esplora::new(client, "http://esplora/api");
+
+        let entry = futures::executor::block_on(registry.upgrade(&esplora, usdt.asset_id)).unwrap();
+        assert_eq!(entry.contract, usdt.contract);
+        assert_eq!(entry.issuance_prevout, usdt.issuance_prevout);
+        assert_eq!(entry.version, usdt.version);
+        // The registry values are kept
+        assert_eq!(entry.issuer_pubkey.as_deref(), Some("registry"));
+
+        registry.merge(entry.clone());
+        let asset = &registry.assets[&usdt.asset_id];
+        assert_eq!(asset.asset_entry.as_ref(), Some(&entry));
+
+        // Full entries are not fetched again
+        let offline = Esplora::new(StubClient::default(), "http://esplora/api");
+        let upgraded =
+            futures::executor::block_on(registry.upgrade(&offline, usdt.asset_id)).unwrap();
+        assert_eq!(upgraded, entry);
+        let unknown = AssetId::default();
+        assert!(matches!(
+            futures::executor::block_on(registry.upgrade(&esplora, unknown)),
+            Err(RegistryError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn load_full() {
+        let index = r#"{
+            "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2": {
+                "asset_id": "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2",
+                "contract": { "name": "Tether USD" },
+                "name": "Tether USD",
+                "precision": 8,
+                "ticker": "USDt"
             }
-            None => Err(serde::de::Error::missing_field("")),
+        }"#;
+        let sources = Sources {
+            format: Format::Full,
+            index: Source::Bytes(index.as_bytes().to_vec()),
+            ..Default::default()
+        };
+        let registry = futures::executor::block_on(Registry::load(&sources)).unwrap();
+        assert_eq!(registry.assets.len(), 1);
+        let asset = registry.assets.values().next().unwrap();
+        assert!(asset.asset_entry.as_ref().unwrap().contract.is_some());
+
+        // Not the minimal index in place of the missing full one
+        let sources = Sources {
+            format: Format::Full,
+            index: Source::Embedded,
+            ..Default::default()
+        };
+        let registry = futures::executor::block_on(Registry::load(&sources));
+        if cfg!(feature = "full") {
+            assert!(registry
+                .unwrap()
+                .assets
+                .values()
+                .any(|x| x.asset_entry.as_ref().is_some_and(|x| x.contract.is_some())));
+        } else {
+            assert!(matches!(registry, Err(RegistryError::MissingSource(_))));
         }
     }
 }
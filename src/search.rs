@@ -0,0 +1,307 @@
+//! Ranked, typo tolerant search over the registry.
+//!
+//! Ticker, name, domain and asset id of each asset are indexed in normalized
+//! form, see `normalize`, so that searching `usdt` finds `USDt` and `cafe`
+//! finds `Café`. Matches are ranked by `Tier`, then by `Metadata::weight`.
+
+use std::cmp::Reverse;
+use std::ops::Range;
+
+use elements::AssetId;
+use indexmap::IndexMap;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::asset::Asset;
+
+/// Lowercase the text and strip its diacritics, eg. `Café` to `cafe`.
+pub fn normalize(text: &str) -> String {
+    text.nfd()
+        .filter(|x| !is_combining_mark(*x))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// How an asset matches a query, from the weakest to the strongest match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tier {
+    /// The asset id contains the query.
+    Id,
+    /// A word of the ticker or of the name is within a few typos of the
+    /// query.
+    Fuzzy,
+    /// The domain contains the query.
+    Domain,
+    /// The ticker contains the query, eg. a word of `L-BTC` for `btc`.
+    Ticker,
+    /// The name contains the query.
+    Name,
+    /// The ticker or the name starts with the query.
+    Prefix,
+    /// The ticker is the query.
+    ExactTicker,
+}
+
+/// An asset matching a query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub asset_id: AssetId,
+    pub tier: Tier,
+    /// The rank of the match: the tier, lowered by the typos of a fuzzy
+    /// match and boosted by the weight of the asset. A weight never lifts a
+    /// match above a stronger tier.
+    pub score: u32,
+}
+
+/// The normalized searchable fields of an asset.
+#[derive(Clone, Debug)]
+struct Entry {
+    ticker: String,
+    name: String,
+    domain: String,
+    id: String,
+    /// The words of ticker and name, for fuzzy matching.
+    words: Vec<Vec<char>>,
+    weight: u16,
+}
+
+impl Entry {
+    fn new(asset: &Asset) -> Self {
+        let ticker = normalize(asset.ticker().unwrap_or_default());
+        let name = normalize(asset.name().unwrap_or_default());
+        let words = ticker
+            .split(|x: char| !x.is_alphanumeric())
+            .chain(name.split(|x: char| !x.is_alphanumeric()))
+            .filter(|x| !x.is_empty())
+            .map(|x| x.chars().collect())
+            .collect();
+        Entry {
+            ticker,
+            name,
+            domain: normalize(asset.domain().unwrap_or_default()),
+            id: asset.asset_id.to_string(),
+            words,
+            weight: asset.weight().unwrap_or_default(),
+        }
+    }
+
+    /// Match the entry against a normalized, non empty query.
+    fn rank(&self, query: &str) -> Option<(Tier, u32)> {
+        if self.ticker == query {
+            Some((Tier::ExactTicker, 0))
+        } else if self.ticker.starts_with(query) || self.name.starts_with(query) {
+            Some((Tier::Prefix, 0))
+        } else if self.name.contains(query) {
+            Some((Tier::Name, 0))
+        } else if self.ticker.contains(query) {
+            Some((Tier::Ticker, 0))
+        } else if self.domain.contains(query) {
+            Some((Tier::Domain, 0))
+        } else if let Some(typos) = self.typos(query) {
+            Some((Tier::Fuzzy, typos))
+        } else if self.id.contains(query) {
+            Some((Tier::Id, 0))
+        } else {
+            None
+        }
+    }
+
+    /// The fewest typos between the query and the words of ticker and name,
+    /// or their beginnings, when within the tolerance for the query length.
+    fn typos(&self, query: &str) -> Option<u32> {
+        let query: Vec<char> = query.chars().collect();
+        let tolerance = match query.len() {
+            0..=3 => return None,
+            4..=7 => 1,
+            _ => 2,
+        };
+        self.words
+            .iter()
+            // The distance is at least the difference of the lengths
+            .filter(|word| query.len().saturating_sub(word.len()) <= tolerance)
+            .map(|word| {
+                let prefix = distance(&query, &word[..word.len().min(query.len())]);
+                if word.len() > query.len() && word.len() - query.len() <= tolerance {
+                    prefix.min(distance(&query, word))
+                } else {
+                    prefix
+                }
+            })
+            .min()
+            .filter(|x| *x <= tolerance as u32)
+    }
+}
+
+/// Score of each tier, spaced so that weight and typos stay within a tier.
+fn score(tier: Tier, typos: u32, weight: u16) -> u32 {
+    (tier as u32 + 1) * 1000 - typos * 200 + u32::from(weight.min(100))
+}
+
+/// The optimal string alignment distance: insertions, deletions,
+/// substitutions and transpositions of adjacent characters.
+fn distance(a: &[char], b: &[char]) -> u32 {
+    // Row major matrix of the distances between the prefixes of a and b
+    let width = b.len() + 1;
+    let mut d = vec![0u32; (a.len() + 1) * width];
+    for i in 0..=a.len() {
+        d[i * width] = i as u32;
+    }
+    for (j, cell) in d[..width].iter_mut().enumerate() {
+        *cell = j as u32;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = u32::from(a[i - 1] != b[j - 1]);
+            let mut best = (d[(i - 1) * width + j] + 1)
+                .min(d[i * width + j - 1] + 1)
+                .min(d[(i - 1) * width + j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(d[(i - 2) * width + j - 2] + 1);
+            }
+            d[i * width + j] = best;
+        }
+    }
+    d[a.len() * width + b.len()]
+}
+
+/// The normalized fields of every asset of a registry.
+#[derive(Clone, Debug, Default)]
+pub struct SearchIndex {
+    entries: IndexMap<AssetId, Entry>,
+}
+
+impl SearchIndex {
+    pub fn new<'a>(assets: impl IntoIterator<Item = &'a Asset>) -> Self {
+        SearchIndex {
+            entries: assets
+                .into_iter()
+                .map(|x| (x.asset_id, Entry::new(x)))
+                .collect(),
+        }
+    }
+
+    /// The assets matching the query, best first. Matches with the same score
+    /// keep the order of the index. An empty query matches nothing.
+    pub fn search(&self, query: &str) -> Vec<Match> {
+        let query = normalize(query.trim());
+        if query.is_empty() {
+            return vec![];
+        }
+        let mut matches: Vec<Match> = self
+            .entries
+            .iter()
+            .filter_map(|(asset_id, entry)| {
+                entry.rank(&query).map(|(tier, typos)| Match {
+                    asset_id: *asset_id,
+                    tier,
+                    score: score(tier, typos, entry.weight),
+                })
+            })
+            .collect();
+        matches.sort_by_key(|x| Reverse(x.score));
+        matches
+    }
+}
+
+/// Whether the asset matches the query, see `SearchIndex::search`.
+pub(crate) fn matches(asset: &Asset, query: &str) -> bool {
+    let query = normalize(query.trim());
+    query.is_empty() || Entry::new(asset).rank(&query).is_some()
+}
+
+/// The byte range of the first occurrence of the query in the text, ignoring
+/// case and diacritics, eg. to highlight the match. `None` for fuzzy matches.
+pub fn find(text: &str, query: &str) -> Option<Range<usize>> {
+    let query: Vec<char> = normalize(query.trim()).chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+    // The normalized characters of the text, with the range of the original
+    // character each comes from
+    let mut chars = vec![];
+    let mut ranges = vec![];
+    for (start, x) in text.char_indices() {
+        for folded in normalize(x.encode_utf8(&mut [0; 4])).chars() {
+            chars.push(folded);
+            ranges.push(start..start + x.len_utf8());
+        }
+    }
+    let first = chars
+        .windows(query.len())
+        .position(|x| x == query.as_slice())?;
+    let last = first + query.len() - 1;
+    Some(ranges[first].start..ranges[last].end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Registry;
+
+    const LBTC: &str = "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d";
+    const USDT: &str = "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2";
+
+    fn search(query: &str) -> Vec<Match> {
+        Registry::new().search(query)
+    }
+
+    #[test]
+    fn ticker_word() {
+        let matches = search("btc");
+        let lbtc = matches
+            .iter()
+            .find(|x| x.asset_id.to_string() == LBTC)
+            .expect("L-BTC matches btc");
+        assert_eq!(lbtc.tier, Tier::Ticker);
+    }
+
+    #[test]
+    fn exact_ticker_first() {
+        let matches = search("USDT");
+        assert_eq!(matches[0].asset_id.to_string(), USDT);
+        assert_eq!(matches[0].tier, Tier::ExactTicker);
+    }
+
+    #[test]
+    fn typos() {
+        let matches = search("teher");
+        assert_eq!(matches[0].asset_id.to_string(), USDT);
+        assert_eq!(matches[0].tier, Tier::Fuzzy);
+        assert!(search("   ").is_empty());
+    }
+
+    #[test]
+    fn tiers() {
+        let ticker = |x: &str| x.chars().collect::<Vec<_>>();
+        let entry = Entry {
+            ticker: "l-btc".into(),
+            name: "liquid bitcoin".into(),
+            domain: "blockstream.com".into(),
+            id: LBTC.into(),
+            words: vec![
+                ticker("l"),
+                ticker("btc"),
+                ticker("liquid"),
+                ticker("bitcoin"),
+            ],
+            weight: 0,
+        };
+        assert_eq!(entry.rank("l-btc"), Some((Tier::ExactTicker, 0)));
+        assert_eq!(entry.rank("liq"), Some((Tier::Prefix, 0)));
+        assert_eq!(entry.rank("bitcoin"), Some((Tier::Name, 0)));
+        assert_eq!(entry.rank("btc"), Some((Tier::Ticker, 0)));
+        assert_eq!(entry.rank("stream"), Some((Tier::Domain, 0)));
+        assert_eq!(entry.rank("bitcion"), Some((Tier::Fuzzy, 1)));
+        assert_eq!(entry.rank("6f0279"), Some((Tier::Id, 0)));
+        assert_eq!(entry.rank("ethereum"), None);
+    }
+
+    #[test]
+    fn normalized() {
+        assert_eq!(normalize("Café"), "cafe");
+        assert_eq!(distance(&['a', 'b'], &['b', 'a']), 1);
+        assert_eq!(find("Café Token", "cafe"), Some(0..5));
+        assert_eq!(find("L-BTC", "btc"), Some(2..5));
+        assert_eq!(find("L-BTC", "eth"), None);
+    }
+}
@@ -0,0 +1,102 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::RegistryError;
+use crate::http::{HttpClient, ReqwestClient};
+
+/// Where a registry file is loaded from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// Download the file from the given url, falling back to the embedded
+    /// copy when the download fails.
+    Url(String),
+    /// Parse the file from the given bytes, eg. a local copy.
+    Bytes(Vec<u8>),
+    /// Use the copy embedded in the binary at build time, if any.
+    Embedded,
+}
+
+impl Source {
+    /// Read the content of the source. `embedded` is the copy included in the
+    /// binary, used by `Source::Embedded` and as offline fallback of
+    /// `Source::Url`.
+    pub(crate) async fn read(
+        &self,
+        embedded: Option<&'static str>,
+    ) -> Result<Option<Cow<'_, [u8]>>, RegistryError> {
+        let embedded = embedded.map(|x| Cow::Borrowed(x.as_bytes()));
+        match self {
+            Source::Url(url) => match ReqwestClient::default().get(url).await {
+                Ok(bytes) => Ok(Some(Cow::Owned(bytes))),
+                Err(_) if embedded.is_some() => Ok(embedded),
+                Err(err) => Err(err),
+            },
+            Source::Bytes(bytes) => Ok(Some(Cow::Borrowed(bytes))),
+            Source::Embedded => Ok(embedded),
+        }
+    }
+}
+
+/// The format of the asset index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// `index.minimal.json`: domain, ticker, name and precision of each asset.
+    #[cfg_attr(not(feature = "full"), default)]
+    Minimal,
+    /// `index.json`: the full entries, including the contract and the
+    /// issuance required to verify the assets.
+    #[cfg_attr(feature = "full", default)]
+    Full,
+}
+
+impl Format {
+    pub const ALL: [Format; 2] = [Format::Minimal, Format::Full];
+}
+
+impl FromStr for Format {
+    type Err = RegistryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minimal" => Ok(Format::Minimal),
+            "full" => Ok(Format::Full),
+            _ => Err(RegistryError::InvalidQuery(format!("unknown format {}", s))),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Minimal => write!(f, "minimal"),
+            Format::Full => write!(f, "full"),
+        }
+    }
+}
+
+/// The set of files a `Registry` is built from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sources {
+    /// The format of `index`.
+    pub format: Format,
+    /// The asset index.
+    pub index: Source,
+    /// The icons of the assets, as base64 encoded png.
+    pub icons: Source,
+    /// The enciclopedia metadata: amp, stablecoin, weight, price pair.
+    pub metadata: Source,
+}
+
+impl Default for Sources {
+    /// The copies embedded in the binary. Icons are not embedded to keep the
+    /// binary small, the full index only with the `full` feature.
+    fn default() -> Self {
+        Sources {
+            format: Format::default(),
+            index: Source::Embedded,
+            icons: Source::Embedded,
+            metadata: Source::Embedded,
+        }
+    }
+}
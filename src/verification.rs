@@ -0,0 +1,61 @@
+use elements::AssetId;
+use serde::Serialize;
+
+/// The outcome of a single verification check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// The check succeeded.
+    Pass,
+    /// The data is available but doesn't match.
+    Fail,
+    /// The data needed by the check is not available, eg. for entries of the
+    /// minimal registry which have no contract.
+    Missing,
+}
+
+/// A single verification check, such as the asset id commitment or one of the
+/// fields mirrored from the contract.
+#[derive(Clone, Debug, Serialize)]
+pub struct Check {
+    /// What is checked, eg. `asset id` or `ticker`.
+    pub name: &'static str,
+    pub status: Status,
+    /// Human readable details on the outcome, eg. the mismatching values.
+    pub detail: Option<String>,
+}
+
+impl Check {
+    pub(crate) fn new(name: &'static str, status: Status, detail: Option<String>) -> Self {
+        Check {
+            name,
+            status,
+            detail,
+        }
+    }
+}
+
+/// The field-by-field result of the verification of an `AssetEntry`
+/// against its contract.
+#[derive(Clone, Debug, Serialize)]
+pub struct VerificationReport {
+    pub asset_id: AssetId,
+    pub checks: Vec<Check>,
+}
+
+impl VerificationReport {
+    /// Whether every check passed.
+    pub fn verified(&self) -> bool {
+        !self.checks.is_empty() && self.checks.iter().all(|x| x.status == Status::Pass)
+    }
+
+    /// The checks that didn't pass, either failing or missing data.
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|x| x.status != Status::Pass)
+    }
+
+    /// The check with the given name.
+    pub fn check(&self, name: &str) -> Option<&Check> {
+        self.checks.iter().find(|x| x.name == name)
+    }
+}
@@ -0,0 +1,229 @@
+use std::rc::Rc;
+
+use elements::AssetId;
+use indexmap::IndexMap;
+use yew::{html, Callback, Children, Component, Context, ContextProvider, Html, Properties};
+
+use enciclopedia::asset::Asset;
+use enciclopedia::asset_entry::AssetEntry;
+use enciclopedia::error::RegistryError;
+use enciclopedia::import::{Conflict, Import};
+use enciclopedia::registry::Registry;
+use enciclopedia::source::Sources;
+
+/// The loading state of the registry of the store.
+#[derive(Clone, Debug)]
+pub enum LoadState {
+    Loading,
+    Loaded,
+    /// The sources couldn't be loaded, the previous registry is kept.
+    Failed(Rc<RegistryError>),
+}
+
+/// The registry shared with the components through the context of
+/// `RegistryProvider`. Components get it with `Scope::context`, whose
+/// callback is called on each change.
+#[derive(Clone)]
+pub struct RegistryStore {
+    /// The registry loaded from the sources, with the imports merged.
+    pub registry: Rc<Registry>,
+    /// The registry as loaded from the sources.
+    base: Rc<Registry>,
+    /// The metadata merged into the registry, in order.
+    pub imports: Rc<Vec<Import>>,
+    /// The conflicts of the last merge of the imports.
+    pub conflicts: Rc<Vec<Conflict>>,
+    /// The full entries fetched for the assets opened, kept apart from the
+    /// registry so that opening an asset doesn't copy the whole registry.
+    /// See `RegistryStore::asset`.
+    entries: Rc<IndexMap<AssetId, AssetEntry>>,
+    pub state: LoadState,
+    /// The sources the registry is loaded from. Sources being loaded by
+    /// `RegistryStore::swap` replace them once loaded.
+    pub sources: Rc<Sources>,
+    /// Incremented each time the registry is loaded or the imports merged,
+    /// so that components can tell a new registry from a change of the
+    /// current one.
+    pub generation: u64,
+    /// Incremented on each change of the store.
+    version: u64,
+    load: Callback<Option<Sources>>,
+    merge: Callback<AssetEntry>,
+    set_imports: Callback<Vec<Import>>,
+}
+
+impl RegistryStore {
+    /// Load the registry again from its sources.
+    pub fn reload(&self) {
+        self.load.emit(None);
+    }
+
+    /// Load the registry from other sources, eg. another network or format.
+    /// The current registry is kept until the new one is loaded.
+    pub fn swap(&self, sources: Sources) {
+        self.load.emit(Some(sources));
+    }
+
+    /// Keep the full entry fetched for an asset, replacing its entry in
+    /// `RegistryStore::asset`. Lists and searches keep the registry entry.
+    pub fn merge(&self, entry: AssetEntry) {
+        self.merge.emit(entry);
+    }
+
+    /// The asset of the registry, with the full entry fetched for it if any.
+    pub fn asset(&self, asset_id: &AssetId) -> Option<Asset> {
+        let mut asset = self.registry.assets.get(asset_id)?.clone();
+        if let Some(entry) = self.entries.get(asset_id) {
+            asset.asset_entry = Some(entry.clone());
+        }
+        Some(asset)
+    }
+
+    /// The full entry fetched for the asset, see `RegistryStore::merge`.
+    pub fn entry(&self, asset_id: &AssetId) -> Option<&AssetEntry> {
+        self.entries.get(asset_id)
+    }
+
+    /// Merge the metadata of the imports into the registry as loaded from the
+    /// sources, replacing the previous imports, see `Registry::import`.
+    pub fn set_imports(&self, imports: Vec<Import>) {
+        self.set_imports.emit(imports);
+    }
+
+    /// Whether a registry has been loaded, even if a reload is in progress
+    /// or failed.
+    pub fn is_loaded(&self) -> bool {
+        self.generation > 0
+    }
+}
+
+impl PartialEq for RegistryStore {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+    }
+}
+
+pub enum StoreMsg {
+    Load(Option<Sources>),
+    /// The result of the load with the given sequence number, from the
+    /// given sources.
+    SetRegistry(u64, Rc<Sources>, Result<Registry, RegistryError>),
+    Merge(AssetEntry),
+    SetImports(Vec<Import>),
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RegistryProviderProps {
+    /// The sources loaded on creation.
+    pub sources: Rc<Sources>,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Load the registry asynchronously and provide it to its children as a
+/// `RegistryStore` context.
+pub struct RegistryProvider {
+    store: RegistryStore,
+    /// Incremented on each load, to ignore the results of stale loads.
+    load_seq: u64,
+}
+
+impl RegistryProvider {
+    fn changed(&mut self) {
+        self.store.version += 1;
+    }
+
+    /// Rebuild the registry from the loaded one and the imports.
+    fn merge_imports(&mut self) {
+        let mut registry = (*self.store.base).clone();
+        self.store.conflicts = Rc::new(registry.import(&self.store.imports));
+        self.store.registry = Rc::new(registry);
+        self.store.generation += 1;
+    }
+}
+
+impl Component for RegistryProvider {
+    type Message = StoreMsg;
+    type Properties = RegistryProviderProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_message(StoreMsg::Load(None));
+        Self {
+            store: RegistryStore {
+                registry: Rc::new(Registry::from_assets(Default::default())),
+                base: Rc::new(Registry::from_assets(Default::default())),
+                imports: Rc::new(vec![]),
+                conflicts: Rc::new(vec![]),
+                entries: Rc::new(IndexMap::new()),
+                state: LoadState::Loading,
+                sources: ctx.props().sources.clone(),
+                generation: 0,
+                version: 0,
+                load: ctx.link().callback(StoreMsg::Load),
+                merge: ctx.link().callback(StoreMsg::Merge),
+                set_imports: ctx.link().callback(StoreMsg::SetImports),
+            },
+            load_seq: 0,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            StoreMsg::Load(sources) => {
+                let sources = sources.map_or(self.store.sources.clone(), Rc::new);
+                self.load_seq += 1;
+                let seq = self.load_seq;
+                ctx.link().send_future(async move {
+                    let registry = Registry::load(&sources).await;
+                    StoreMsg::SetRegistry(seq, sources, registry)
+                });
+                self.store.state = LoadState::Loading;
+                self.changed();
+                true
+            }
+            StoreMsg::SetRegistry(seq, sources, result) => {
+                if seq != self.load_seq {
+                    return false;
+                }
+                match result {
+                    Ok(registry) => {
+                        // The entries fetched for other sources, eg. another
+                        // network, may not match the new registry
+                        if sources != self.store.sources {
+                            self.store.entries = Default::default();
+                        }
+                        self.store.sources = sources;
+                        self.store.base = Rc::new(registry);
+                        self.store.state = LoadState::Loaded;
+                        self.merge_imports();
+                    }
+                    Err(err) => self.store.state = LoadState::Failed(Rc::new(err)),
+                }
+                self.changed();
+                true
+            }
+            StoreMsg::Merge(entry) => {
+                Rc::make_mut(&mut self.store.entries).insert(entry.asset_id, entry);
+                self.changed();
+                true
+            }
+            StoreMsg::SetImports(imports) => {
+                self.store.imports = Rc::new(imports);
+                // Merged on load otherwise
+                if self.store.is_loaded() {
+                    self.merge_imports();
+                }
+                self.changed();
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <ContextProvider<RegistryStore> context={ self.store.clone() }>
+                { ctx.props().children.clone() }
+            </ContextProvider<RegistryStore>>
+        }
+    }
+}